@@ -0,0 +1,113 @@
+use chrono::NaiveDate;
+
+use crate::Position;
+use crate::prices::PriceHistory;
+
+/// The benchmark's close-to-close return from `from` to `to`, using the
+/// last close on or before each date.
+pub(crate) fn period_return(history: &PriceHistory, from: NaiveDate, to: NaiveDate) -> Option<f64> {
+    let (_, start) = history.latest(from)?;
+    let (_, end) = history.latest(to)?;
+    (start.close > 0.0).then(|| end.close / start.close - 1.0)
+}
+
+/// A position's return next to the benchmark's over the same days.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Comparison {
+    pub(crate) benchmark: f64,
+    /// ROI minus the benchmark return.
+    pub(crate) excess: f64,
+}
+
+pub(crate) fn compare(pos: &Position, history: &PriceHistory) -> Option<Comparison> {
+    let benchmark = period_return(history, pos.purchase_date, pos.sale_date)?;
+    Some(Comparison {
+        benchmark,
+        excess: pos.roi_pct() - benchmark,
+    })
+}
+
+/// Cumulative PnL by sale date, and what the same money would have made in
+/// the benchmark over the same holding periods.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct EquityCurve {
+    /// (days since the first purchase, cumulative PnL)
+    pub(crate) portfolio: Vec<(f64, f64)>,
+    pub(crate) benchmark: Vec<(f64, f64)>,
+    /// Positions that did better than the benchmark, out of `compared`.
+    pub(crate) beat: usize,
+    pub(crate) compared: usize,
+}
+
+/// Positions the benchmark has no prices for count as flat in its line.
+pub(crate) fn equity_curve(positions: &[&Position], history: Option<&PriceHistory>) -> EquityCurve {
+    let mut curve = EquityCurve::default();
+    let Some(start) = positions.iter().map(|p| p.purchase_date).min() else {
+        return curve;
+    };
+    let mut by_sale: Vec<&Position> = positions.to_vec();
+    by_sale.sort_by_key(|p| p.sale_date);
+
+    let (mut pnl, mut benchmark_pnl) = (0.0, 0.0);
+    curve.portfolio.push((0.0, 0.0));
+    curve.benchmark.push((0.0, 0.0));
+    for pos in by_sale {
+        let day = (pos.sale_date - start).num_days() as f64;
+        pnl += pos.roi_value();
+        if let Some(comparison) = history.and_then(|h| compare(pos, h)) {
+            benchmark_pnl += pos.invested() * comparison.benchmark;
+            curve.compared += 1;
+            if comparison.excess > 0.0 {
+                curve.beat += 1;
+            }
+        }
+        curve.portfolio.push((day, pnl));
+        curve.benchmark.push((day, benchmark_pnl));
+    }
+    curve
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn positions_are_compared_over_their_own_days() {
+        let spy = PriceHistory::parse(
+            "Date,Close\n2026-03-02,500\n2026-03-03,510\n2026-03-06,525\n2026-03-09,490\n",
+        )
+        .unwrap();
+        let trade = |sale_price, purchase_date, sale_date| Position {
+            ticker: "KO".into(),
+            cost_per_share: 100.0,
+            quantity: 10.0,
+            sale_price,
+            purchase_date,
+            sale_date,
+            ..Position::default()
+        };
+        // +3% while SPY did +5%; the weekend uses Friday's close.
+        let lagging = trade(103.0, day(2), day(8));
+        let comparison = compare(&lagging, &spy).unwrap();
+        assert!((comparison.benchmark - 0.05).abs() < 1e-9);
+        assert!((comparison.excess + 0.02).abs() < 1e-9);
+
+        // -1% while SPY fell 3.92%.
+        let leading = trade(99.0, day(3), day(9));
+        let early = trade(110.0, day(1), day(2));
+        assert!(compare(&early, &spy).is_none());
+
+        let curve = equity_curve(&[&leading, &early, &lagging], Some(&spy));
+        assert_eq!((curve.beat, curve.compared), (1, 2));
+        assert_eq!(
+            curve.portfolio,
+            vec![(0.0, 0.0), (1.0, 100.0), (7.0, 130.0), (8.0, 120.0)]
+        );
+        let benchmark: Vec<f64> = curve.benchmark.iter().map(|p| p.1.round()).collect();
+        assert_eq!(benchmark, vec![0.0, 0.0, 50.0, 11.0]);
+    }
+}
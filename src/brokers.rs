@@ -0,0 +1,619 @@
+use chrono::NaiveDate;
+
+use crate::dividends::DividendKind;
+use crate::import::{
+    DetectedMapping, ImportReport, ImportRow, MatchStatus, Side, SkippedRow, Trade, Transaction,
+    ValueFormats, match_lots_fifo, sanitize_header,
+};
+use crate::options::{CONTRACT_SIZE, OptionContract, is_closure_code, parse_symbol};
+use crate::{Position, parse_date, parse_date_any, parse_f64, parse_ticker};
+
+/// A built-in import layout for one broker export, recognized by its header row.
+pub(crate) struct BrokerProfile {
+    pub(crate) name: &'static str,
+    /// Sanitized header names that must all appear in the header row.
+    signature: &'static [&'static str],
+    layout: Layout,
+}
+
+enum Layout {
+    /// One row per closed lot.
+    ClosedLots(LotColumns),
+    /// One row per fill; lots are matched first-in, first-out.
+    Trades(TradeColumns),
+}
+
+enum Amount {
+    PerShare(&'static str),
+    Total(&'static str),
+}
+
+struct LotColumns {
+    ticker: &'static str,
+    quantity: &'static str,
+    acquired: &'static str,
+    sold: &'static str,
+    cost: Amount,
+    proceeds: Amount,
+    /// Column and value a row must carry to be a lot, e.g. E*TRADE's "Sell" records.
+    row_filter: Option<(&'static str, &'static str)>,
+}
+
+struct TradeColumns {
+    ticker: &'static str,
+    date: &'static str,
+    side: &'static str,
+    quantity: &'static str,
+    price: &'static str,
+    /// Column and value a row must carry to count, e.g. Webull's "Filled" orders.
+    row_filter: Option<(&'static str, &'static str)>,
+    /// Cash amount column, read for dividend rows.
+    amount: Option<&'static str>,
+    /// Free-text column a dividend row's record date can be read from.
+    description: Option<&'static str>,
+}
+
+pub(crate) const BROKER_PROFILES: &[BrokerProfile] = &[
+    BrokerProfile {
+        name: "Fidelity realized gain/loss",
+        signature: &[
+            "symbolcusip",
+            "dateacquired",
+            "datesold",
+            "proceeds",
+            "costbasis",
+        ],
+        layout: Layout::ClosedLots(LotColumns {
+            ticker: "symbolcusip",
+            quantity: "quantity",
+            acquired: "dateacquired",
+            sold: "datesold",
+            cost: Amount::Total("costbasis"),
+            proceeds: Amount::Total("proceeds"),
+            row_filter: None,
+        }),
+    },
+    BrokerProfile {
+        name: "Schwab realized gain/loss",
+        signature: &[
+            "symbol",
+            "openeddate",
+            "closeddate",
+            "proceedspershare",
+            "costpershare",
+        ],
+        layout: Layout::ClosedLots(LotColumns {
+            ticker: "symbol",
+            quantity: "quantity",
+            acquired: "openeddate",
+            sold: "closeddate",
+            cost: Amount::PerShare("costpershare"),
+            proceeds: Amount::PerShare("proceedspershare"),
+            row_filter: None,
+        }),
+    },
+    BrokerProfile {
+        name: "E*TRADE gains & losses",
+        signature: &[
+            "recordtype",
+            "symbol",
+            "dateacquired",
+            "datesold",
+            "adjustedcostbasispershare",
+        ],
+        layout: Layout::ClosedLots(LotColumns {
+            ticker: "symbol",
+            quantity: "quantity",
+            acquired: "dateacquired",
+            sold: "datesold",
+            cost: Amount::PerShare("adjustedcostbasispershare"),
+            proceeds: Amount::PerShare("proceedspershare"),
+            row_filter: Some(("recordtype", "sell")),
+        }),
+    },
+    BrokerProfile {
+        name: "Vanguard cost basis",
+        signature: &[
+            "symbol",
+            "dateacquired",
+            "datesold",
+            "totalcost",
+            "proceeds",
+        ],
+        layout: Layout::ClosedLots(LotColumns {
+            ticker: "symbol",
+            quantity: "quantity",
+            acquired: "dateacquired",
+            sold: "datesold",
+            cost: Amount::Total("totalcost"),
+            proceeds: Amount::Total("proceeds"),
+            row_filter: None,
+        }),
+    },
+    BrokerProfile {
+        name: "Robinhood account activity",
+        signature: &[
+            "activitydate",
+            "instrument",
+            "transcode",
+            "quantity",
+            "price",
+        ],
+        layout: Layout::Trades(TradeColumns {
+            ticker: "instrument",
+            date: "activitydate",
+            side: "transcode",
+            quantity: "quantity",
+            price: "price",
+            row_filter: None,
+            amount: Some("amount"),
+            description: Some("description"),
+        }),
+    },
+    BrokerProfile {
+        name: "Webull orders",
+        signature: &[
+            "symbol",
+            "side",
+            "status",
+            "filled",
+            "avgprice",
+            "filledtime",
+        ],
+        layout: Layout::Trades(TradeColumns {
+            ticker: "symbol",
+            date: "filledtime",
+            side: "side",
+            quantity: "filled",
+            price: "avgprice",
+            row_filter: Some(("status", "filled")),
+            amount: None,
+            description: None,
+        }),
+    },
+];
+
+/// Finds the first row (within the top of the file) whose header matches a
+/// built-in profile.
+pub(crate) fn detect_broker(
+    records: &[(usize, Vec<String>)],
+) -> Option<(&'static BrokerProfile, usize)> {
+    records
+        .iter()
+        .enumerate()
+        .take(50)
+        .find_map(|(i, (_, fields))| {
+            let names: Vec<String> = fields.iter().map(|f| sanitize_header(f)).collect();
+            BROKER_PROFILES
+                .iter()
+                .find(|p| p.signature.iter().all(|s| names.iter().any(|n| n == s)))
+                .map(|p| (p, i))
+        })
+}
+
+pub(crate) fn parse_broker_records(
+    profile: &BrokerProfile,
+    records: &[(usize, Vec<String>)],
+    header_row: usize,
+    today: NaiveDate,
+) -> ImportReport {
+    let (line, headers) = &records[header_row];
+    let names: Vec<String> = headers.iter().map(|h| sanitize_header(h)).collect();
+    let col = |name: &str| names.iter().position(|n| n == name);
+    let body = &records[header_row + 1..];
+
+    let mut report = ImportReport::default();
+    let mapped = |label: &'static str, name: &str, fields: &mut Vec<(&'static str, usize)>| {
+        let idx = col(name);
+        if let Some(i) = idx {
+            fields.push((label, i));
+        }
+        idx
+    };
+    let mut fields = Vec::new();
+
+    match &profile.layout {
+        Layout::ClosedLots(c) => {
+            let amount_col = |a: &Amount| match a {
+                Amount::PerShare(name) | Amount::Total(name) => *name,
+            };
+            let ticker = mapped("Ticker", c.ticker, &mut fields);
+            let qty = mapped("Quantity", c.quantity, &mut fields);
+            let cost = mapped("Cost", amount_col(&c.cost), &mut fields);
+            let proceeds = mapped("Proceeds", amount_col(&c.proceeds), &mut fields);
+            let acquired = mapped("Purchase date", c.acquired, &mut fields);
+            let sold = mapped("Sale date", c.sold, &mut fields);
+            let filter = c
+                .row_filter
+                .and_then(|(name, value)| Some((col(name)?, value)));
+
+            for (line_no, row) in body {
+                if is_free_text(row) {
+                    continue;
+                }
+                let get =
+                    |i: Option<usize>| i.and_then(|i| row.get(i)).map(|s| s.as_str()).unwrap_or("");
+                if let Some((i, expected)) = filter {
+                    let value = get(Some(i)).trim();
+                    if !value.eq_ignore_ascii_case(expected) {
+                        report.skipped.push(SkippedRow {
+                            line: *line_no,
+                            reason: format!("{value} record, not a lot"),
+                        });
+                        continue;
+                    }
+                }
+                let lot = build_lot(
+                    get(ticker),
+                    get(qty),
+                    (&c.cost, get(cost)),
+                    (&c.proceeds, get(proceeds)),
+                    get(acquired),
+                    get(sold),
+                );
+                match lot {
+                    Ok(position) => report.rows.push(ImportRow {
+                        line: *line_no,
+                        position,
+                        selected: true,
+                        status: MatchStatus::New,
+                    }),
+                    Err(reason) => report.skipped.push(SkippedRow {
+                        line: *line_no,
+                        reason,
+                    }),
+                }
+            }
+        }
+        Layout::Trades(c) => {
+            let ticker = mapped("Ticker", c.ticker, &mut fields);
+            let date = mapped("Date", c.date, &mut fields);
+            let side = mapped("Side", c.side, &mut fields);
+            let qty = mapped("Quantity", c.quantity, &mut fields);
+            let price = mapped("Price", c.price, &mut fields);
+            let amount = c.amount.and_then(col);
+            let description = c.description.and_then(col);
+            let filter = c
+                .row_filter
+                .and_then(|(name, value)| Some((col(name)?, value)));
+
+            let mut trades = Vec::new();
+            for (line_no, row) in body {
+                if is_free_text(row) {
+                    continue;
+                }
+                let get =
+                    |i: Option<usize>| i.and_then(|i| row.get(i)).map(|s| s.as_str()).unwrap_or("");
+                if let Some((i, expected)) = filter {
+                    let value = get(Some(i)).trim();
+                    if !value.eq_ignore_ascii_case(expected) {
+                        report.skipped.push(SkippedRow {
+                            line: *line_no,
+                            reason: format!("{value} order, not a fill"),
+                        });
+                        continue;
+                    }
+                }
+                let raw_side = get(side).trim();
+                if amount.is_some() && DividendKind::from_code(raw_side).is_some() {
+                    match build_income(
+                        *line_no,
+                        get(ticker),
+                        get(date),
+                        raw_side,
+                        get(amount),
+                        get(description),
+                    ) {
+                        Ok(income) => report.transactions.push(income),
+                        Err(reason) => report.skipped.push(SkippedRow {
+                            line: *line_no,
+                            reason,
+                        }),
+                    }
+                    continue;
+                }
+                // Expiry, exercise and assignment close an option at zero.
+                let (trade_side, raw_price) = if is_closure_code(raw_side) {
+                    (Some(Side::Sell), "0")
+                } else {
+                    (Side::parse(raw_side), get(price))
+                };
+                let Some(trade_side) = trade_side else {
+                    report.skipped.push(SkippedRow {
+                        line: *line_no,
+                        reason: format!("{raw_side} activity is not a trade"),
+                    });
+                    continue;
+                };
+                match build_trade(
+                    *line_no,
+                    get(ticker),
+                    get(date),
+                    trade_side,
+                    get(qty),
+                    raw_price,
+                ) {
+                    Ok(trade) => trades.push(trade),
+                    Err(reason) => report.skipped.push(SkippedRow {
+                        line: *line_no,
+                        reason,
+                    }),
+                }
+            }
+            match_lots_fifo(trades, today, &mut report);
+        }
+    }
+
+    report.mapping = Some(DetectedMapping {
+        line: *line,
+        headers: headers.clone(),
+        fields,
+        formats: ValueFormats::default(),
+        profile: Some(profile.name.to_string()),
+    });
+    report
+}
+
+/// Footnotes and disclaimers occupy a single cell; they aren't data rows.
+fn is_free_text(row: &[String]) -> bool {
+    row.iter().filter(|c| !c.trim().is_empty()).count() <= 1
+}
+
+/// Strips the CUSIP suffix brokers append, e.g. `AAPL(037833100)`.
+fn clean_ticker(raw: &str) -> Result<String, String> {
+    parse_ticker(strip_cusip(raw))
+}
+
+/// Like `clean_ticker`, but also reads OCC option symbols.
+fn clean_symbol(raw: &str) -> Result<(String, Option<OptionContract>), String> {
+    parse_symbol(strip_cusip(raw))
+}
+
+fn strip_cusip(raw: &str) -> &str {
+    raw.split('(').next().unwrap_or("").trim()
+}
+
+/// Takes the date from a timestamp such as `01/05/2026 09:31:02 EST`.
+fn clean_date(raw: &str, label: &str) -> Result<chrono::NaiveDate, String> {
+    parse_date(raw.split_whitespace().next().unwrap_or(""), label)
+}
+
+fn per_share(amount: (&Amount, &str), qty: f64, label: &str) -> Result<f64, String> {
+    let value = parse_f64(amount.1, label)?.abs();
+    match amount.0 {
+        Amount::PerShare(_) => Ok(value),
+        Amount::Total(_) if qty > 0.0 => Ok(value / qty),
+        Amount::Total(_) => Err("Quantity must be positive".into()),
+    }
+}
+
+fn build_lot(
+    ticker: &str,
+    qty: &str,
+    cost: (&Amount, &str),
+    proceeds: (&Amount, &str),
+    acquired: &str,
+    sold: &str,
+) -> Result<Position, String> {
+    let (ticker, option) = clean_symbol(ticker)?;
+    let quantity = parse_f64(qty, "quantity")?.abs();
+    // Option quantities count contracts; totals are divided down to a share.
+    let shares = match option {
+        Some(_) => quantity * CONTRACT_SIZE,
+        None => quantity,
+    };
+    let cost_per_share = per_share(cost, shares, "cost")?;
+    let sale_price = per_share(proceeds, shares, "proceeds")?;
+    let purchase_date = clean_date(acquired, "purchase date")?;
+    let sale_date = clean_date(sold, "sale date")?;
+    if sale_date < purchase_date {
+        return Err("Sale date cannot be before purchase date".into());
+    }
+    let mut position = Position {
+        cost_per_share,
+        quantity,
+        sale_price,
+        purchase_date,
+        sale_date,
+        ..Default::default()
+    };
+    position.set_symbol(ticker, option);
+    Ok(position)
+}
+
+fn build_income(
+    line: usize,
+    ticker: &str,
+    date: &str,
+    code: &str,
+    amount: &str,
+    description: &str,
+) -> Result<Transaction, String> {
+    Ok(Transaction::Income {
+        line,
+        ticker: clean_ticker(ticker)?,
+        date: clean_date(date, "payment date")?,
+        ex_date: record_date(description),
+        kind: code.to_string(),
+        amount: parse_f64(amount, "amount")?.abs(),
+    })
+}
+
+/// The record date in a description such as Robinhood's
+/// "Cash Div: R/D 2026-01-10 P/D 2026-01-15". With T+1 settlement the record
+/// date is also the ex-date.
+fn record_date(description: &str) -> Option<NaiveDate> {
+    let (_, rest) = description.split_once("R/D")?;
+    let date = rest.split_whitespace().next()?;
+    parse_date_any(date).ok()
+}
+
+fn build_trade(
+    line: usize,
+    ticker: &str,
+    date: &str,
+    side: Side,
+    qty: &str,
+    price: &str,
+) -> Result<Trade, String> {
+    let (ticker, option) = clean_symbol(ticker)?;
+    Ok(Trade {
+        line,
+        ticker,
+        option,
+        date: clean_date(date, "trade date")?,
+        side,
+        quantity: parse_f64(qty, "quantity")?.abs(),
+        price: parse_f64(price, "price")?,
+        fees: 0.0,
+        id: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::read_csv_records;
+
+    fn load(fixture: &str) -> (&'static str, ImportReport) {
+        load_on(fixture, date("2026-03-31"))
+    }
+
+    fn load_on(fixture: &str, today: NaiveDate) -> (&'static str, ImportReport) {
+        let path = format!("{}/tests/fixtures/{fixture}", env!("CARGO_MANIFEST_DIR"));
+        let records = read_csv_records(&path).expect("fixture readable");
+        let (profile, header_row) = detect_broker(&records).expect("profile detected");
+        (
+            profile.name,
+            parse_broker_records(profile, &records, header_row, today),
+        )
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn fidelity_realized_lots_use_totals() {
+        let (name, report) = load("fidelity_realized_gain_loss.csv");
+        assert_eq!(name, "Fidelity realized gain/loss");
+        assert_eq!(report.rows.len(), 3);
+        let first = &report.rows[0].position;
+        assert_eq!(first.ticker, "AAPL");
+        assert_eq!(first.quantity, 10.0);
+        assert!((first.cost_per_share - 185.25).abs() < 1e-9);
+        assert!((first.sale_price - 192.10).abs() < 1e-9);
+        assert_eq!(first.purchase_date, date("2026-01-05"));
+        assert_eq!(first.sale_date, date("2026-01-20"));
+        // "Various" acquisition date can't be imported as a single lot.
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].reason.contains("purchase date"));
+    }
+
+    #[test]
+    fn schwab_realized_lots_use_per_share_columns() {
+        let (name, report) = load("schwab_realized_gain_loss.csv");
+        assert_eq!(name, "Schwab realized gain/loss");
+        assert_eq!(report.rows.len(), 2);
+        let nvda = &report.rows[1].position;
+        assert_eq!(nvda.ticker, "NVDA");
+        assert_eq!(nvda.quantity, 5.0);
+        assert!((nvda.cost_per_share - 480.0).abs() < 1e-9);
+        assert!((nvda.sale_price - 455.5).abs() < 1e-9);
+        assert_eq!(nvda.sale_date, date("2026-02-03"));
+    }
+
+    #[test]
+    fn etrade_keeps_only_sell_records() {
+        let (name, report) = load("etrade_gains_losses.csv");
+        assert_eq!(name, "E*TRADE gains & losses");
+        assert_eq!(report.rows.len(), 2);
+        assert!(report.rows.iter().all(|r| r.position.ticker == "MSFT"));
+        assert!((report.rows[0].position.cost_per_share - 400.0).abs() < 1e-9);
+        assert!((report.rows[1].position.sale_price - 421.0).abs() < 1e-9);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].reason.contains("Summary"));
+    }
+
+    #[test]
+    fn vanguard_cost_basis_divides_totals() {
+        let (name, report) = load("vanguard_cost_basis.csv");
+        assert_eq!(name, "Vanguard cost basis");
+        assert_eq!(report.rows.len(), 2);
+        let vti = &report.rows[0].position;
+        assert_eq!(vti.ticker, "VTI");
+        assert!((vti.cost_per_share - 250.0).abs() < 1e-9);
+        assert!((vti.sale_price - 262.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn robinhood_activity_matches_fifo() {
+        let (name, report) = load("robinhood_activity.csv");
+        assert_eq!(name, "Robinhood account activity");
+        // 15 AMD sold against lots of 10 @ 100 and 10 @ 110.
+        assert_eq!(report.rows.len(), 2);
+        let (a, b) = (&report.rows[0].position, &report.rows[1].position);
+        assert_eq!((a.quantity, a.cost_per_share), (10.0, 100.0));
+        assert_eq!((b.quantity, b.cost_per_share), (5.0, 110.0));
+        assert!(report.rows.iter().all(|r| r.position.sale_price == 120.0));
+        assert_eq!(a.purchase_date, date("2026-01-05"));
+        assert_eq!(b.sale_date, date("2026-01-20"));
+        let reasons: Vec<&str> = report.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert!(reasons.iter().any(|r| r.contains("ACH")));
+        assert!(reasons.iter().any(|r| r.contains("still open")));
+        let dividends = report.dividends();
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].ticker, "AMD");
+        assert_eq!(dividends[0].amount, 2.5);
+        assert_eq!(dividends[0].pay_date, date("2026-01-15"));
+        assert_eq!(dividends[0].ex_date, Some(date("2026-01-10")));
+    }
+
+    #[test]
+    fn robinhood_options_close_on_expiry() {
+        let (_, report) = load("robinhood_options.csv");
+        assert_eq!(report.rows.len(), 3);
+        assert!(report.skipped.is_empty());
+        let sold = &report.rows[0].position;
+        let contract = sold.option.expect("option contract");
+        assert_eq!(sold.ticker, "AAPL");
+        assert_eq!(contract.strike, 150.0);
+        assert_eq!(sold.multiplier, 100.0);
+        assert_eq!((sold.quantity, sold.cost_per_share), (2.0, 4.5));
+        assert!((sold.roi_value() - 300.0).abs() < 1e-9);
+        // The OEXP row closes the last contract at zero.
+        let expired = &report.rows[1].position;
+        assert_eq!((expired.quantity, expired.sale_price), (1.0, 0.0));
+        assert_eq!(expired.sale_date, date("2026-02-20"));
+        // The put has no closing row; it expired worthless on its expiry date.
+        let put = &report.rows[2].position;
+        assert_eq!(put.ticker, "TSLA");
+        assert_eq!(put.sale_date, date("2026-01-16"));
+        assert!((put.roi_value() + 620.0).abs() < 1e-9);
+
+        // Before its expiry the put is still open.
+        let (_, report) = load_on("robinhood_options.csv", date("2026-01-16"));
+        assert_eq!(report.rows.len(), 2);
+        assert!(report.skipped[0].reason.contains("TSLA"));
+        assert!(report.skipped[0].reason.contains("still open"));
+    }
+
+    #[test]
+    fn webull_orders_skip_unfilled() {
+        let (name, report) = load("webull_orders.csv");
+        assert_eq!(name, "Webull orders");
+        assert_eq!(report.rows.len(), 1);
+        let p = &report.rows[0].position;
+        assert_eq!(p.ticker, "TSLA");
+        assert_eq!(p.quantity, 3.0);
+        assert!((p.cost_per_share - 240.5).abs() < 1e-9);
+        assert!((p.sale_price - 251.75).abs() < 1e-9);
+        assert_eq!(p.purchase_date, date("2026-01-06"));
+        assert!(
+            report
+                .skipped
+                .iter()
+                .any(|s| s.reason.contains("Cancelled"))
+        );
+    }
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeSet;
+
+use crate::input::{InputKind, TextInput};
+use crate::instruments::{Instrument, InstrumentRegistry};
+use crate::options::parse_symbol;
+use crate::{Position, parse_account, parse_f64};
+
+/// An edit applied to every marked position at once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum BulkAction {
+    Tag,
+    Fees,
+    Rename,
+    Account,
+}
+
+impl BulkAction {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            BulkAction::Tag => "Tag (prefix - to remove)",
+            BulkAction::Fees => "Set fees",
+            BulkAction::Rename => "Rename ticker",
+            BulkAction::Account => "Move to account (empty for none)",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            BulkAction::Tag => BulkAction::Fees,
+            BulkAction::Fees => BulkAction::Rename,
+            BulkAction::Rename => BulkAction::Account,
+            BulkAction::Account => BulkAction::Tag,
+        }
+    }
+
+    fn input_kind(self) -> InputKind {
+        match self {
+            BulkAction::Fees => InputKind::Number,
+            BulkAction::Tag | BulkAction::Rename | BulkAction::Account => InputKind::Text,
+        }
+    }
+}
+
+/// The bulk-edit prompt: which action and its value.
+#[derive(Clone, Debug)]
+pub(crate) struct BulkEdit {
+    pub(crate) action: BulkAction,
+    pub(crate) input: TextInput,
+    pub(crate) error: Option<String>,
+}
+
+impl BulkEdit {
+    pub(crate) fn new() -> Self {
+        Self {
+            action: BulkAction::Tag,
+            input: TextInput::new(BulkAction::Tag.input_kind()),
+            error: None,
+        }
+    }
+
+    /// Switches to the next action; the value is cleared since its kind changes.
+    pub(crate) fn cycle_action(&mut self) {
+        self.action = self.action.next();
+        self.input = TextInput::new(self.action.input_kind());
+        self.error = None;
+    }
+
+    /// Applies the action to the marked positions and describes what changed.
+    /// Renamed positions are classified again with `instruments`.
+    pub(crate) fn apply(
+        &self,
+        positions: &mut [Position],
+        marked: &BTreeSet<usize>,
+        instruments: &InstrumentRegistry,
+    ) -> Result<String, String> {
+        let targets: Vec<usize> = marked
+            .iter()
+            .copied()
+            .filter(|&i| i < positions.len())
+            .collect();
+        if targets.is_empty() {
+            return Err("No positions marked".into());
+        }
+        let value = self.input.value().trim();
+        let count = targets.len();
+        match self.action {
+            BulkAction::Tag => {
+                let (remove, tag) = match value.strip_prefix('-') {
+                    Some(tag) => (true, tag),
+                    None => (false, value),
+                };
+                if tag.trim().is_empty() {
+                    return Err("Tag cannot be empty".into());
+                }
+                for i in targets {
+                    if remove {
+                        positions[i].remove_tag(tag);
+                    } else {
+                        positions[i].add_tag(tag);
+                    }
+                }
+                let verb = if remove { "Removed" } else { "Added" };
+                Ok(format!("{verb} tag on {count} positions"))
+            }
+            BulkAction::Fees => {
+                let fees = parse_f64(value, "fees")?;
+                for i in targets {
+                    positions[i].fees = fees;
+                }
+                Ok(format!("Set fees on {count} positions"))
+            }
+            BulkAction::Rename => {
+                let (ticker, option) = parse_symbol(value)?;
+                for i in targets {
+                    let pos = &mut positions[i];
+                    // The old symbol's contract size doesn't carry over.
+                    if !pos.instrument.is_equity() {
+                        pos.instrument = Instrument::Equity;
+                        pos.multiplier = 1.0;
+                    }
+                    pos.set_symbol(ticker.clone(), option);
+                    instruments.classify(pos);
+                }
+                let symbol = option.map_or(ticker.clone(), |contract| contract.label(&ticker));
+                Ok(format!("Renamed {count} positions to {symbol}"))
+            }
+            BulkAction::Account => {
+                let account = parse_account(value);
+                for i in targets {
+                    positions[i].account = account.clone();
+                }
+                Ok(match account {
+                    Some(account) => format!("Moved {count} positions to {account}"),
+                    None => format!("Cleared the account of {count} positions"),
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bulk(action: BulkAction, value: &str) -> BulkEdit {
+        let mut edit = BulkEdit::new();
+        while edit.action != action {
+            edit.cycle_action();
+        }
+        edit.input.set_value(value);
+        edit
+    }
+
+    #[test]
+    fn applies_only_to_marked_positions() {
+        let mut positions = vec![
+            Position::default(),
+            Position::default(),
+            Position::default(),
+        ];
+        let marked = BTreeSet::from([0, 2]);
+        let registry = InstrumentRegistry::builtin();
+        bulk(BulkAction::Tag, "Earnings Gap")
+            .apply(&mut positions, &marked, &registry)
+            .unwrap();
+        bulk(BulkAction::Tag, "earnings gap")
+            .apply(&mut positions, &marked, &registry)
+            .unwrap();
+        assert_eq!(positions[0].tags, ["earnings-gap"]);
+        assert!(positions[1].tags.is_empty());
+        bulk(BulkAction::Tag, "-earnings-gap")
+            .apply(&mut positions, &marked, &registry)
+            .unwrap();
+        assert!(positions[2].tags.is_empty());
+
+        bulk(BulkAction::Rename, "fb")
+            .apply(&mut positions, &marked, &registry)
+            .unwrap();
+        assert_eq!(positions[2].ticker, "FB");
+        assert_ne!(positions[1].ticker, "FB");
+        assert!(
+            bulk(BulkAction::Fees, "")
+                .apply(&mut positions, &marked, &registry)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn renaming_reclassifies_the_instrument() {
+        let mut positions = vec![Position::default()];
+        let marked = BTreeSet::from([0]);
+        let registry = InstrumentRegistry::builtin();
+        let rename = |positions: &mut [Position], symbol: &str| {
+            bulk(BulkAction::Rename, symbol)
+                .apply(positions, &marked, &registry)
+                .unwrap()
+        };
+
+        rename(&mut positions, "ESH6");
+        assert_eq!(positions[0].instrument, Instrument::Future);
+        assert_eq!(positions[0].multiplier, 50.0);
+
+        let message = rename(&mut positions, "AAPL  260220C00150000");
+        assert!(message.ends_with("AAPL 150C 2026-02-20"), "{message}");
+        assert_eq!(positions[0].ticker, "AAPL");
+        assert_eq!(positions[0].instrument, Instrument::Option);
+        assert_eq!(positions[0].multiplier, 100.0);
+
+        rename(&mut positions, "ko");
+        assert_eq!(positions[0].option, None);
+        assert_eq!(positions[0].instrument, Instrument::Equity);
+        assert_eq!(positions[0].multiplier, 1.0);
+    }
+}
@@ -0,0 +1,148 @@
+use std::{fs::File, path::Path};
+
+use serde_json::json;
+
+use crate::export::write_positions;
+use crate::import::DuplicatePolicy;
+use crate::{
+    App, DATA_FILE, DataFile, Mode, Position, load_data, parse_account, summarize_positions,
+};
+
+const USAGE: &str = "usage: roi                         start the TUI
+       roi import <file> [--skip|--replace|--all] [--sheet <name>]
+                         [--account <name>] [--no-dividends]
+                                  import a CSV, Excel/ODS, OFX/QFX or
+                                  IBKR Flex XML file; matches are
+                                  skipped by default
+       roi export <file.csv>      write all positions, with tags and
+                                  notes, as CSV
+       roi stats [--account <name>]
+                                  print summary and risk figures as
+                                  JSON, in the base currency";
+
+/// The app over the stored positions. Unlike the TUI it starts empty when
+/// there is no data file, so demo positions are never exported or saved.
+fn open_app() -> Result<App, String> {
+    let data = if Path::new(DATA_FILE).exists() {
+        load_data()?
+    } else {
+        DataFile::default()
+    };
+    Ok(App::with_data(data))
+}
+
+/// Runs a command-line subcommand instead of the TUI.
+pub(crate) fn run(args: &[String]) -> Result<(), String> {
+    match args[0].as_str() {
+        "import" => import(&args[1..]),
+        "export" => export(&args[1..]),
+        "stats" => stats(&args[1..]),
+        "help" | "-h" | "--help" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        other => Err(format!("unknown command: {other}\n{USAGE}")),
+    }
+}
+
+fn import(args: &[String]) -> Result<(), String> {
+    let mut path = None;
+    let mut policy = DuplicatePolicy::Skip;
+    let mut sheet = None;
+    let mut app_account = None;
+    let mut dividends = true;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--sheet" => sheet = Some(args.next().ok_or("--sheet needs a sheet name")?),
+            "--account" => {
+                let name = args.next().ok_or("--account needs an account name")?;
+                app_account = parse_account(name);
+            }
+            "--skip" => policy = DuplicatePolicy::Skip,
+            "--replace" => policy = DuplicatePolicy::Replace,
+            "--all" => policy = DuplicatePolicy::ImportAnyway,
+            "--no-dividends" => dividends = false,
+            flag if flag.starts_with("--") => return Err(format!("unknown option: {flag}")),
+            file => path = Some(file),
+        }
+    }
+    let path = path.ok_or(USAGE)?;
+
+    let mut app = open_app()?;
+    if let Some(err) = &app.config_error {
+        eprintln!("{err}; saved profiles are not used and settings are not saved");
+    }
+    app.import_account = app_account;
+    let mode = match sheet {
+        Some(sheet) => app.preview_sheet(path, sheet)?,
+        None => app.preview_import(path)?,
+    };
+    if let Some(picker) = &app.sheet_picker {
+        return Err(format!(
+            "Workbook has several sheets ({}); choose one with --sheet",
+            picker.sheets.join(", ")
+        ));
+    }
+    if mode == Mode::Splits {
+        println!("{}", app.splits_message.take().unwrap_or_default());
+        return Ok(());
+    }
+    if mode != Mode::ImportPreview {
+        return Err(
+            "Header not recognized; map its columns once from the TUI import dialog".into(),
+        );
+    }
+    if let Some(preview) = app.import_preview.as_mut() {
+        preview.policy = policy;
+        preview.include_dividends = dividends;
+        for skipped in &preview.report.skipped {
+            eprintln!("line {}: skipped, {}", skipped.line, skipped.reason);
+        }
+        for (line, note) in preview.report.income_notes() {
+            eprintln!("line {line}: {note}");
+        }
+    }
+    println!("{}", app.commit_import());
+    Ok(())
+}
+
+fn export(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err(USAGE.into());
+    };
+    let app = open_app()?;
+    let file = File::create(path).map_err(|e| format!("Failed to create {path}: {e}"))?;
+    write_positions(file, &app.positions)?;
+    println!("Exported {} positions to {path}", app.positions.len());
+    Ok(())
+}
+
+fn stats(args: &[String]) -> Result<(), String> {
+    let mut app = open_app()?;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--account" => {
+                let name = args.next().ok_or("--account needs an account name")?;
+                app.account = parse_account(name);
+            }
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let base = app.base_positions();
+    let shown: Vec<&Position> = app.filtered_indices().iter().map(|&i| &base[i]).collect();
+    let stats = json!({
+        "base_currency": app.fx.base,
+        "account": app.account,
+        "positions": shown.len(),
+        "summary": summarize_positions(&shown),
+        "risk_free_rate": app.config.risk_free_rate,
+        "risk": app.risk(),
+        "taxable_gains": app.taxable_gains(),
+        "r_multiples": app.r_stats(),
+    });
+    let json = serde_json::to_string_pretty(&stats).map_err(|e| e.to_string())?;
+    println!("{json}");
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::import::ImportProfile;
+use crate::quotes::SourceConfig;
+
+const CONFIG_FILE: &str = "roi_config.json";
+const MAX_RECENT_IMPORTS: usize = 10;
+const DEFAULT_TRASH_RETENTION_DAYS: u32 = 30;
+const DEFAULT_BASE_CURRENCY: &str = "USD";
+const DEFAULT_FX_RATES_FILE: &str = "fx_rates.csv";
+const DEFAULT_INSTRUMENTS_FILE: &str = "instruments.csv";
+const DEFAULT_PRICE_HISTORY_DIR: &str = "prices";
+const DEFAULT_QUOTE_CACHE_FILE: &str = "quotes.json";
+const DEFAULT_QUOTE_REFRESH_MINUTES: u32 = 15;
+const DEFAULT_BENCHMARK: &str = "SPY";
+
+/// User settings stored next to the data file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct Config {
+    /// Column mappings saved from the import wizard, matched by header signature.
+    #[serde(default)]
+    pub(crate) import_profiles: Vec<ImportProfile>,
+    /// Most recently imported files, newest first.
+    #[serde(default)]
+    pub(crate) recent_imports: Vec<String>,
+    /// Deleted positions older than this are purged from the trash; 0 keeps them.
+    #[serde(default = "default_trash_retention_days")]
+    pub(crate) trash_retention_days: u32,
+    /// Accounts such as IRAs whose trades have no tax consequences; they are
+    /// left out of taxable gains.
+    #[serde(default)]
+    pub(crate) tax_advantaged_accounts: Vec<String>,
+    /// Currency totals are reported in; positions without a currency are in it.
+    #[serde(default = "default_base_currency")]
+    pub(crate) base_currency: String,
+    /// Daily rates CSV (`date,currency,rate`) used to convert into the base.
+    #[serde(default = "default_fx_rates_file")]
+    pub(crate) fx_rates_file: String,
+    /// Contract specs (`symbol,type,multiplier,decimals`) for futures and crypto.
+    #[serde(default = "default_instruments_file")]
+    pub(crate) instruments_file: String,
+    /// Directory of daily price CSVs, one per symbol (`AAPL.csv`).
+    #[serde(default = "default_price_history_dir")]
+    pub(crate) price_history_dir: String,
+    /// Where quotes for open positions are fetched from.
+    #[serde(default)]
+    pub(crate) price_source: SourceConfig,
+    /// Fetched quotes, kept between runs.
+    #[serde(default = "default_quote_cache_file")]
+    pub(crate) quote_cache_file: String,
+    /// Quotes are refetched once older than this; 0 refreshes only on `P`.
+    #[serde(default = "default_quote_refresh_minutes")]
+    pub(crate) quote_refresh_minutes: u32,
+    /// Symbol whose price file positions are compared against; empty for none.
+    #[serde(default = "default_benchmark")]
+    pub(crate) benchmark: String,
+    /// Account equity before any recorded trade; the sizing calculator adds
+    /// realized PnL to it.
+    #[serde(default)]
+    pub(crate) starting_balance: f64,
+    /// Annual risk-free rate for Sharpe and Sortino ratios, e.g. 0.04.
+    #[serde(default)]
+    pub(crate) risk_free_rate: f64,
+    /// Returns include the dividends attributed to each position.
+    #[serde(default)]
+    pub(crate) total_return: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            import_profiles: Vec::new(),
+            recent_imports: Vec::new(),
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            tax_advantaged_accounts: Vec::new(),
+            base_currency: default_base_currency(),
+            fx_rates_file: default_fx_rates_file(),
+            instruments_file: default_instruments_file(),
+            price_history_dir: default_price_history_dir(),
+            price_source: SourceConfig::default(),
+            quote_cache_file: default_quote_cache_file(),
+            quote_refresh_minutes: DEFAULT_QUOTE_REFRESH_MINUTES,
+            benchmark: default_benchmark(),
+            risk_free_rate: 0.0,
+            starting_balance: 0.0,
+            total_return: false,
+        }
+    }
+}
+
+fn default_base_currency() -> String {
+    DEFAULT_BASE_CURRENCY.to_string()
+}
+
+fn default_fx_rates_file() -> String {
+    DEFAULT_FX_RATES_FILE.to_string()
+}
+
+fn default_instruments_file() -> String {
+    DEFAULT_INSTRUMENTS_FILE.to_string()
+}
+
+fn default_price_history_dir() -> String {
+    DEFAULT_PRICE_HISTORY_DIR.to_string()
+}
+
+fn default_quote_cache_file() -> String {
+    DEFAULT_QUOTE_CACHE_FILE.to_string()
+}
+
+fn default_quote_refresh_minutes() -> u32 {
+    DEFAULT_QUOTE_REFRESH_MINUTES
+}
+
+fn default_benchmark() -> String {
+    DEFAULT_BENCHMARK.to_string()
+}
+
+fn default_trash_retention_days() -> u32 {
+    DEFAULT_TRASH_RETENTION_DAYS
+}
+
+impl Config {
+    /// Adds a profile, replacing any existing one for the same header signature.
+    pub(crate) fn upsert_profile(&mut self, profile: ImportProfile) {
+        self.import_profiles
+            .retain(|p| p.signature != profile.signature);
+        self.import_profiles.push(profile);
+    }
+
+    pub(crate) fn is_tax_advantaged(&self, account: &str) -> bool {
+        self.tax_advantaged_accounts
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(account))
+    }
+
+    /// Moves `path` to the front of the recent imports list.
+    pub(crate) fn remember_import(&mut self, path: &str) {
+        let path = fs::canonicalize(path)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| path.to_string());
+        self.recent_imports.retain(|p| *p != path);
+        self.recent_imports.insert(0, path);
+        self.recent_imports.truncate(MAX_RECENT_IMPORTS);
+    }
+}
+
+/// A missing file gives the defaults; one that can't be read or parsed is an
+/// error, so the caller can avoid saving over it.
+pub(crate) fn load_config() -> Result<Config, String> {
+    load_config_from(Path::new(CONFIG_FILE))
+}
+
+fn load_config_from(path: &Path) -> Result<Config, String> {
+    if !path.exists() {
+        return Ok(Config::default());
+    }
+    let data =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    serde_json::from_str(&data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub(crate) fn save_config(config: &Config) {
+    let path = Path::new(CONFIG_FILE);
+    if let Ok(json) = serde_json::to_string_pretty(config)
+        && let Err(err) = fs::write(path, json)
+    {
+        eprintln!("Could not save config: {err}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn unreadable_settings_are_an_error() {
+        let path = env::temp_dir().join(format!("roi-config-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let defaults = load_config_from(&path).unwrap();
+        assert_eq!(defaults.benchmark, DEFAULT_BENCHMARK);
+
+        fs::write(&path, r#"{"benchmark": "QQQ", "total_return": true}"#).unwrap();
+        assert_eq!(load_config_from(&path).unwrap().benchmark, "QQQ");
+
+        fs::write(&path, r#"{"benchmark": "QQQ", "#).unwrap();
+        let err = load_config_from(&path).unwrap_err();
+        assert!(err.contains("roi-config"), "{err}");
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,231 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum DividendKind {
+    Qualified,
+    Ordinary,
+    /// Capital-gain distribution from a fund.
+    CapitalGain,
+}
+
+impl DividendKind {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            DividendKind::Qualified => "qualified",
+            DividendKind::Ordinary => "ordinary",
+            DividendKind::CapitalGain => "cap gain",
+        }
+    }
+
+    /// Reads an OFX INCOMETYPE or a broker activity code. Interest and other
+    /// income that isn't a distribution gives `None`.
+    pub(crate) fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_ascii_uppercase().as_str() {
+            "QDIV" | "QUALIFIED" | "QUALIFIED DIVIDEND" => Some(DividendKind::Qualified),
+            "DIV" | "CDIV" | "ORDINARY" | "DIVIDEND" | "ORDINARY DIVIDEND" => {
+                Some(DividendKind::Ordinary)
+            }
+            "CGLONG" | "CGSHORT" | "CAPITAL GAIN" => Some(DividendKind::CapitalGain),
+            _ => None,
+        }
+    }
+}
+
+/// A cash distribution received for a ticker.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Dividend {
+    pub(crate) ticker: String,
+    /// Holders before this date receive the payment; the pay date stands in
+    /// when it isn't known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) ex_date: Option<NaiveDate>,
+    pub(crate) pay_date: NaiveDate,
+    /// Total cash received, in the ticker's currency.
+    pub(crate) amount: f64,
+    pub(crate) kind: DividendKind,
+    /// Only positions in this account share the payment, when set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) account: Option<String>,
+}
+
+impl Dividend {
+    /// The same payment, whatever ex-date either copy carries.
+    fn same_payment(&self, other: &Dividend) -> bool {
+        Dividend {
+            ex_date: other.ex_date,
+            ..self.clone()
+        } == *other
+    }
+
+    pub(crate) fn ex_date(&self) -> NaiveDate {
+        self.ex_date.unwrap_or(self.pay_date)
+    }
+
+    /// Shares held over the ex-date: bought before it and not sold until on
+    /// or after it. Options on the ticker receive nothing.
+    pub(crate) fn entitles(&self, pos: &Position) -> bool {
+        let ex = self.ex_date();
+        pos.ticker.eq_ignore_ascii_case(&self.ticker)
+            && pos.option.is_none()
+            && (self.account.is_none() || pos.account == self.account)
+            && pos.purchase_date < ex
+            && pos.sale_date >= ex
+    }
+}
+
+/// Income attributed to each position, in the same order. A payment is
+/// shared by the positions held over its ex-date in proportion to their
+/// quantity.
+pub(crate) fn attribute(positions: &[Position], dividends: &[Dividend]) -> Vec<f64> {
+    let mut income = vec![0.0; positions.len()];
+    for dividend in dividends {
+        let holders: Vec<usize> = (0..positions.len())
+            .filter(|&i| dividend.entitles(&positions[i]))
+            .collect();
+        let shares: f64 = holders
+            .iter()
+            .map(|&i| positions[i].quantity * positions[i].multiplier)
+            .sum();
+        if shares <= 0.0 {
+            continue;
+        }
+        for i in holders {
+            income[i] += dividend.amount * positions[i].quantity * positions[i].multiplier / shares;
+        }
+    }
+    income
+}
+
+/// Dividends paid to no position, e.g. for lots not entered yet.
+pub(crate) fn unattributed<'a>(
+    positions: &[Position],
+    dividends: &'a [Dividend],
+) -> Vec<&'a Dividend> {
+    dividends
+        .iter()
+        .filter(|d| !positions.iter().any(|p| d.entitles(p)))
+        .collect()
+}
+
+/// Adds payments not already in the ledger; a listed payment without an
+/// ex-date takes the new copy's. Returns how many were added.
+pub(crate) fn merge_dividends(ledger: &mut Vec<Dividend>, new: Vec<Dividend>) -> usize {
+    let before = ledger.len();
+    for dividend in new {
+        match ledger.iter_mut().find(|d| d.same_payment(&dividend)) {
+            Some(listed) => listed.ex_date = listed.ex_date.or(dividend.ex_date),
+            None => ledger.push(dividend),
+        }
+    }
+    ledger.sort_by(|a, b| {
+        a.pay_date
+            .cmp(&b.pay_date)
+            .then_with(|| a.ticker.cmp(&b.ticker))
+    });
+    ledger.len() - before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    fn lot(quantity: f64, bought: NaiveDate, sold: NaiveDate) -> Position {
+        Position {
+            ticker: "KO".into(),
+            cost_per_share: 60.0,
+            quantity,
+            sale_price: 60.0,
+            purchase_date: bought,
+            sale_date: sold,
+            ..Position::default()
+        }
+    }
+
+    #[test]
+    fn shares_payments_among_holders_over_the_ex_date() {
+        let positions = vec![
+            lot(100.0, day(3, 1), day(3, 20)),
+            lot(300.0, day(3, 10), day(3, 14)),
+            // Bought on the ex-date: not entitled.
+            lot(50.0, day(3, 14), day(4, 1)),
+            // Sold the day before: not entitled.
+            lot(50.0, day(3, 1), day(3, 13)),
+        ];
+        let dividend = Dividend {
+            ticker: "ko".into(),
+            ex_date: Some(day(3, 14)),
+            pay_date: day(4, 1),
+            amount: 200.0,
+            kind: DividendKind::Qualified,
+            account: None,
+        };
+        let income = attribute(&positions, std::slice::from_ref(&dividend));
+        assert_eq!(income, vec![50.0, 150.0, 0.0, 0.0]);
+        assert!(unattributed(&positions, std::slice::from_ref(&dividend)).is_empty());
+        assert_eq!(unattributed(&positions[2..], &[dividend]).len(), 1);
+    }
+
+    #[test]
+    fn a_sale_before_the_pay_date_still_gets_the_payment() {
+        // Sold after the ex-date but before the payment arrived.
+        let swing = lot(100.0, day(3, 2), day(3, 16));
+        let mut dividend = Dividend {
+            ticker: "KO".into(),
+            ex_date: Some(day(3, 13)),
+            pay_date: day(4, 1),
+            amount: 48.5,
+            kind: DividendKind::Ordinary,
+            account: None,
+        };
+        assert!(dividend.entitles(&swing));
+        assert_eq!(
+            attribute(std::slice::from_ref(&swing), &[dividend.clone()]),
+            vec![48.5]
+        );
+        // Without the ex-date the pay date stands in and the sale misses it.
+        dividend.ex_date = None;
+        assert!(!dividend.entitles(&swing));
+    }
+
+    #[test]
+    fn merging_skips_payments_already_listed() {
+        let dividend = Dividend {
+            ticker: "KO".into(),
+            ex_date: None,
+            pay_date: day(4, 1),
+            amount: 12.5,
+            kind: DividendKind::from_code("CDIV").unwrap(),
+            account: None,
+        };
+        let mut ledger = vec![dividend.clone()];
+        let added = merge_dividends(
+            &mut ledger,
+            vec![
+                dividend.clone(),
+                Dividend {
+                    pay_date: day(7, 1),
+                    ..dividend
+                },
+            ],
+        );
+        assert_eq!(added, 1);
+        assert_eq!(ledger.len(), 2);
+
+        // A re-import that knows the ex-date fills it in rather than adding a copy.
+        let dated = Dividend {
+            ex_date: Some(day(3, 13)),
+            ..ledger[0].clone()
+        };
+        assert_eq!(merge_dividends(&mut ledger, vec![dated]), 0);
+        assert_eq!(ledger[0].ex_date, Some(day(3, 13)));
+        assert_eq!(DividendKind::from_code("interest"), None);
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::Write;
+
+use crate::{DATE_FMT, Position};
+
+const HEADER: [&str; 15] = [
+    "Ticker",
+    "Cost Per Share",
+    "Quantity",
+    "Sale Price",
+    "Purchase Date",
+    "Sale Date",
+    "Lot ID",
+    "Fees",
+    "Currency",
+    "Multiplier",
+    "Account",
+    "Tags",
+    "Notes",
+    "Stop Price",
+    "Target Price",
+];
+
+/// Writes positions as CSV. The column names are ones the importer
+/// recognizes, so an export can be imported again with everything but the
+/// open flag and split history.
+pub(crate) fn write_positions<W: Write>(writer: W, positions: &[Position]) -> Result<(), String> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    let err = |e: csv::Error| format!("Failed to write CSV: {e}");
+    wtr.write_record(HEADER).map_err(err)?;
+    for pos in positions {
+        wtr.write_record([
+            pos.symbol(),
+            pos.cost_per_share.to_string(),
+            pos.quantity.to_string(),
+            pos.sale_price.to_string(),
+            pos.purchase_date.format(DATE_FMT).to_string(),
+            pos.sale_date.format(DATE_FMT).to_string(),
+            pos.lot_id.clone().unwrap_or_default(),
+            pos.fees.to_string(),
+            pos.currency.clone().unwrap_or_default(),
+            pos.multiplier.to_string(),
+            pos.account.clone().unwrap_or_default(),
+            pos.tags.join("; "),
+            pos.notes.clone(),
+            pos.stop_price.map(|p| p.to_string()).unwrap_or_default(),
+            pos.target_price.map(|p| p.to_string()).unwrap_or_default(),
+        ])
+        .map_err(err)?;
+    }
+    wtr.flush().map_err(|e| format!("Failed to write CSV: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{NativeCells, parse_import_records, read_csv_records};
+    use crate::options::parse_symbol;
+
+    #[test]
+    fn exported_csv_imports_again() {
+        let stock = Position {
+            ticker: "AAPL".into(),
+            cost_per_share: 150.25,
+            quantity: 10.0,
+            sale_price: 162.0,
+            purchase_date: chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            sale_date: chrono::NaiveDate::from_ymd_opt(2026, 2, 10).unwrap(),
+            lot_id: Some("L-17".into()),
+            fees: 2.5,
+            currency: Some("CAD".into()),
+            account: Some("Taxable".into()),
+            tags: vec!["breakout".into(), "earnings".into()],
+            notes: "Bought the gap, \"sold\" too early.\nNext time trail the stop.".into(),
+            stop_price: Some(145.0),
+            target_price: Some(170.5),
+            ..Position::default()
+        };
+        let mut option = Position {
+            cost_per_share: 3.1,
+            quantity: 2.0,
+            sale_price: 4.25,
+            multiplier: 10.0,
+            ..stock.clone()
+        };
+        let (underlying, contract) = parse_symbol("AAPL260220C00150000").unwrap();
+        option.set_symbol(underlying, contract);
+        option.tags.clear();
+        option.notes.clear();
+        let positions = vec![stock, option];
+        let path = std::env::temp_dir().join(format!("roi-export-{}.csv", std::process::id()));
+        write_positions(std::fs::File::create(&path).unwrap(), &positions).unwrap();
+
+        let records = read_csv_records(path.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let header = &records[0].1;
+        let row = &records[1].1;
+        assert_eq!(row[11], "breakout; earnings");
+        assert_eq!(row[12], positions[0].notes);
+        assert_eq!(header.len(), row.len());
+
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        let report = parse_import_records(&records, &NativeCells::new(), &[], today);
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+        let imported: Vec<Position> = report.rows.into_iter().map(|r| r.position).collect();
+        assert_eq!(imported, positions);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
+
+use chrono::NaiveDate;
+use quick_xml::{Reader, events::Event};
+
+use crate::import::{
+    DetectedMapping, ImportReport, ImportRow, MatchStatus, SkippedRow, ValueFormats,
+};
+use crate::instruments::Instrument;
+use crate::options::parse_symbol;
+use crate::{Position, parse_number};
+
+/// An element from the Flex statement with its attributes and source line.
+struct Element {
+    line: usize,
+    attrs: HashMap<String, String>,
+}
+
+impl Element {
+    fn get(&self, name: &str) -> &str {
+        self.attrs.get(name).map(|s| s.as_str()).unwrap_or("")
+    }
+
+    fn number(&self, name: &str) -> Option<f64> {
+        parse_number(self.get(name))
+    }
+}
+
+/// Reads an Interactive Brokers Flex Query XML statement. Each closed lot
+/// (`<Lot>` rows in Trades, or `<ClosedLot>` rows) becomes a position; the
+/// closing price and commission come from the execution that closed it. A
+/// lot listed in both sections is imported once.
+pub(crate) fn parse_flex_file(path: &str) -> Result<ImportReport, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    parse_flex_xml(&data)
+}
+
+pub(crate) fn parse_flex_xml(data: &str) -> Result<ImportReport, String> {
+    let mut reader = Reader::from_str(data);
+    let mut executions: HashMap<String, Element> = HashMap::new();
+    let mut lots: Vec<Element> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut is_flex = false;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("Line {}: {e}", line_at(data, start)))?;
+        let el = match event {
+            Event::Start(el) | Event::Empty(el) => el,
+            Event::Eof => break,
+            _ => continue,
+        };
+        let name = String::from_utf8_lossy(el.name().as_ref()).into_owned();
+        if name == "FlexQueryResponse" || name == "FlexStatement" {
+            is_flex = true;
+        }
+        if !matches!(name.as_str(), "Trade" | "Lot" | "ClosedLot") {
+            continue;
+        }
+
+        let mut attrs = HashMap::new();
+        for attr in el.attributes().flatten() {
+            let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+            let value = attr
+                .unescape_value()
+                .map(|v| v.into_owned())
+                .unwrap_or_default();
+            attrs.insert(key, value);
+        }
+        let element = Element {
+            // Skip leading whitespace so the line points at the element itself.
+            line: line_at(data, start + leading_ws(&data[start..])),
+            attrs,
+        };
+
+        let closed_lot = name != "Trade" || element.get("levelOfDetail") == "CLOSED_LOT";
+        if closed_lot {
+            if seen.insert(lot_id(&element)) {
+                lots.push(element);
+            }
+        } else if element.get("levelOfDetail").is_empty()
+            || element.get("levelOfDetail") == "EXECUTION"
+        {
+            let id = element.get("transactionID").to_string();
+            executions.insert(id, element);
+        }
+    }
+
+    if !is_flex {
+        return Err("Not an Interactive Brokers Flex Query statement".into());
+    }
+    if lots.is_empty() {
+        return Err(
+            "No closed lots found; enable Closed Lots in the Flex query's Trades section".into(),
+        );
+    }
+
+    let mut report = ImportReport::default();
+    for lot in &lots {
+        let closing = executions.get(lot.get("transactionID"));
+        match build_lot(lot, closing) {
+            Ok(position) => report.rows.push(ImportRow {
+                line: lot.line,
+                position,
+                selected: true,
+                status: MatchStatus::New,
+            }),
+            Err(reason) => report.skipped.push(SkippedRow {
+                line: lot.line,
+                reason,
+            }),
+        }
+    }
+    report.mapping = Some(DetectedMapping {
+        line: lots[0].line,
+        headers: Vec::new(),
+        fields: Vec::new(),
+        formats: ValueFormats::default(),
+        profile: Some("Interactive Brokers Flex Query (closed lots)".into()),
+    });
+    Ok(report)
+}
+
+fn build_lot(lot: &Element, closing: Option<&Element>) -> Result<Position, String> {
+    // US option symbols are OCC symbols; the multiplier comes from the lot.
+    let (ticker, option) = parse_symbol(lot.get("symbol"))?;
+    // A lot closed by a buy was short; its basis is negative.
+    if lot.get("buySell").to_ascii_uppercase().starts_with("BUY")
+        || lot.number("cost").is_some_and(|c| c < 0.0)
+    {
+        return Err("short lots are not supported".into());
+    }
+    let quantity = lot
+        .number("quantity")
+        .map(f64::abs)
+        .filter(|q| *q > 0.0)
+        .ok_or("Invalid quantity")?;
+    let multiplier = lot.number("multiplier").filter(|m| *m > 0.0).unwrap_or(1.0);
+    // `cost` is the lot's total basis, opening commission included.
+    let cost = lot.number("cost").map(f64::abs).ok_or("Invalid cost")?;
+    let sale_price = closing
+        .and_then(|c| c.number("tradePrice"))
+        .or_else(|| lot.number("closePrice"))
+        .or_else(|| lot.number("tradePrice"))
+        .ok_or("No closing price for lot")?;
+    // The closing commission is shared across the lots that execution closed.
+    let fees = closing
+        .map(|c| {
+            let commission = c.number("ibCommission").unwrap_or(0.0).abs();
+            let closed = c.number("quantity").map(f64::abs).unwrap_or(quantity);
+            if closed > 0.0 {
+                commission * quantity / closed
+            } else {
+                commission
+            }
+        })
+        .unwrap_or(0.0);
+    let purchase_date = flex_date(lot.get("openDateTime")).ok_or("Invalid open date")?;
+    let sale_date = flex_date(lot.get("tradeDate"))
+        .or_else(|| flex_date(lot.get("dateTime")))
+        .ok_or("Invalid trade date")?;
+    if sale_date < purchase_date {
+        return Err("Sale date cannot be before purchase date".into());
+    }
+
+    let currency = Some(lot.get("currency").trim().to_ascii_uppercase()).filter(|c| !c.is_empty());
+
+    Ok(Position {
+        ticker,
+        cost_per_share: cost / (quantity * multiplier),
+        quantity,
+        sale_price,
+        purchase_date,
+        sale_date,
+        lot_id: Some(lot_id(lot)),
+        fees,
+        currency,
+        multiplier,
+        option,
+        instrument: match option {
+            Some(_) => Instrument::Option,
+            None => Instrument::parse(lot.get("assetCategory")).unwrap_or_default(),
+        },
+        ..Position::default()
+    })
+}
+
+/// The closing execution and the opening time identify a lot in either section.
+fn lot_id(lot: &Element) -> String {
+    format!(
+        "ibkr:{}:{}",
+        lot.get("transactionID"),
+        lot.get("openDateTime")
+    )
+}
+
+/// Flex dates come as `yyyyMMdd`, optionally followed by `;HHmmss`, or in
+/// the dashed/slashed forms selectable in the query settings.
+fn flex_date(raw: &str) -> Option<NaiveDate> {
+    let date = raw.split([';', ',', ' ', 'T']).next()?.trim();
+    NaiveDate::parse_from_str(date, "%Y%m%d")
+        .or_else(|_| NaiveDate::parse_from_str(date, "%Y-%m-%d"))
+        .or_else(|_| NaiveDate::parse_from_str(date, "%m/%d/%Y"))
+        .ok()
+}
+
+fn line_at(data: &str, offset: usize) -> usize {
+    let end = offset.min(data.len());
+    data.as_bytes()[..end]
+        .iter()
+        .filter(|b| **b == b'\n')
+        .count()
+        + 1
+}
+
+fn leading_ws(s: &str) -> usize {
+    s.len() - s.trim_start().len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture() -> ImportReport {
+        let path = format!(
+            "{}/tests/fixtures/ibkr_flex.xml",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        parse_flex_file(&path).expect("fixture parses")
+    }
+
+    #[test]
+    fn stock_lots_use_closing_execution() {
+        let report = fixture();
+        let aapl: Vec<&Position> = report
+            .rows
+            .iter()
+            .map(|r| &r.position)
+            .filter(|p| p.ticker == "AAPL" && p.option.is_none())
+            .collect();
+        assert_eq!(aapl.len(), 2);
+        let first = aapl[0];
+        assert_eq!(first.quantity, 60.0);
+        assert!((first.cost_per_share - 185.0).abs() < 1e-9);
+        assert!((first.sale_price - 192.5).abs() < 1e-9);
+        // 1.00 commission on the 100-share close, split 60/40.
+        assert!((first.fees - 0.6).abs() < 1e-9);
+        assert!((aapl[1].fees - 0.4).abs() < 1e-9);
+        assert_eq!(
+            first.purchase_date,
+            NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()
+        );
+        assert_eq!(
+            first.sale_date,
+            NaiveDate::from_ymd_opt(2026, 1, 20).unwrap()
+        );
+        assert_eq!(first.currency.as_deref(), Some("USD"));
+        assert!(first.lot_id.as_deref().unwrap().starts_with("ibkr:5002"));
+    }
+
+    #[test]
+    fn option_lots_carry_multiplier_and_currency() {
+        let report = fixture();
+        let opt = report
+            .rows
+            .iter()
+            .map(|r| &r.position)
+            .find(|p| p.multiplier == 100.0)
+            .expect("option lot");
+        assert_eq!(opt.quantity, 2.0);
+        assert!((opt.cost_per_share - 3.1).abs() < 1e-9);
+        assert!((opt.sale_price - 4.25).abs() < 1e-9);
+        assert!((opt.invested() - 620.0).abs() < 1e-9);
+        assert_eq!(opt.ticker, "AAPL");
+        assert_eq!(opt.option.expect("contract").strike, 150.0);
+
+        let shop = report
+            .rows
+            .iter()
+            .map(|r| &r.position)
+            .find(|p| p.ticker == "SHOP")
+            .expect("CAD lot");
+        assert_eq!(shop.currency.as_deref(), Some("CAD"));
+    }
+
+    #[test]
+    fn bad_lots_are_reported_with_line_numbers() {
+        let report = fixture();
+        let reasons: Vec<&str> = report.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec!["Invalid open date", "short lots are not supported"]
+        );
+        assert!(report.skipped[0].line > 1);
+        assert!(report.rows.iter().all(|r| r.position.ticker != "TSLA"));
+    }
+
+    #[test]
+    fn lots_in_both_sections_import_once() {
+        let report = fixture();
+        let ids: HashSet<&str> = report
+            .rows
+            .iter()
+            .filter_map(|r| r.position.lot_id.as_deref())
+            .collect();
+        assert_eq!(ids.len(), report.rows.len());
+        assert_eq!(report.rows.len(), 4);
+    }
+
+    #[test]
+    fn rejects_non_flex_xml() {
+        assert!(parse_flex_xml("<OFX><Foo/></OFX>").is_err());
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use chrono::NaiveDate;
+
+use crate::{Position, parse_date_any, parse_number};
+
+/// Daily exchange rates into the base currency, read from a CSV with
+/// `date,currency,rate` rows where `rate` is the base-currency value of one
+/// unit of `currency`.
+#[derive(Clone, Debug)]
+pub(crate) struct FxRates {
+    pub(crate) base: String,
+    rates: HashMap<String, BTreeMap<NaiveDate, f64>>,
+}
+
+/// A position's PnL in the base currency, split into the part from the
+/// price move and the part from the exchange rate moving while it was held.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FxBreakdown {
+    pub(crate) buy_rate: f64,
+    pub(crate) sale_rate: f64,
+    pub(crate) pnl: f64,
+    pub(crate) price_pnl: f64,
+    pub(crate) fx_pnl: f64,
+}
+
+impl FxRates {
+    pub(crate) fn empty(base: &str) -> Self {
+        Self {
+            base: base.to_ascii_uppercase(),
+            rates: HashMap::new(),
+        }
+    }
+
+    /// Loads the rates file; a missing file just means no conversions.
+    pub(crate) fn load(path: &str, base: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(Self::empty(base));
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+        let rates = Self::parse(&data, base);
+        if rates.rates.is_empty() && data.lines().count() > 1 {
+            return Err(format!("{path}: no rows in date,currency,rate form"));
+        }
+        Ok(rates)
+    }
+
+    /// Parses rate rows, skipping the header and anything unreadable.
+    fn parse(data: &str, base: &str) -> Self {
+        let mut rates: HashMap<String, BTreeMap<NaiveDate, f64>> = HashMap::new();
+        for line in data.lines() {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [date, currency, rate] = fields[..] else {
+                continue;
+            };
+            let (Ok(date), Some(rate)) = (parse_date_any(date), parse_number(rate)) else {
+                continue;
+            };
+            if rate > 0.0 && !currency.is_empty() {
+                rates
+                    .entry(currency.to_ascii_uppercase())
+                    .or_default()
+                    .insert(date, rate);
+            }
+        }
+        Self {
+            rates,
+            ..Self::empty(base)
+        }
+    }
+
+    /// The rate on `date`, or the latest one before it (weekends, holidays).
+    /// Pence (GBX) fall back to a hundredth of the pound rate.
+    pub(crate) fn rate(&self, currency: &str, date: NaiveDate) -> Option<f64> {
+        let currency = currency.to_ascii_uppercase();
+        if currency == self.base {
+            return Some(1.0);
+        }
+        if let Some(rate) = self
+            .rates
+            .get(&currency)
+            .and_then(|series| series.range(..=date).next_back())
+        {
+            return Some(*rate.1);
+        }
+        match currency.as_str() {
+            "GBX" => self.rate("GBP", date).map(|r| r / 100.0),
+            _ => None,
+        }
+    }
+
+    /// The currency a position is quoted in; positions without one use the base.
+    pub(crate) fn currency_of<'a>(&'a self, pos: &'a Position) -> &'a str {
+        pos.currency.as_deref().unwrap_or(&self.base)
+    }
+
+    /// Base-currency PnL of a foreign position, or `None` for base-currency
+    /// positions and ones missing a rate.
+    pub(crate) fn breakdown(&self, pos: &Position) -> Option<FxBreakdown> {
+        let currency = self.currency_of(pos);
+        if currency.eq_ignore_ascii_case(&self.base) {
+            return None;
+        }
+        let buy_rate = self.rate(currency, pos.purchase_date)?;
+        let sale_rate = self.rate(currency, pos.sale_date)?;
+        let pnl = (pos.proceeds() - pos.fees + pos.income) * sale_rate - pos.invested() * buy_rate;
+        let price_pnl = pos.roi_value() * buy_rate;
+        Some(FxBreakdown {
+            buy_rate,
+            sale_rate,
+            pnl,
+            price_pnl,
+            fx_pnl: pnl - price_pnl,
+        })
+    }
+
+    /// The position restated in the base currency: cost at the purchase-date
+    /// rate, sale price and fees at the sale-date rate. Positions without a
+    /// rate are left unconverted; see `missing`.
+    pub(crate) fn to_base(&self, pos: &Position) -> Position {
+        let currency = self.currency_of(pos);
+        let buy_rate = self.rate(currency, pos.purchase_date).unwrap_or(1.0);
+        let sale_rate = self.rate(currency, pos.sale_date).unwrap_or(1.0);
+        Position {
+            cost_per_share: pos.cost_per_share * buy_rate,
+            sale_price: pos.sale_price * sale_rate,
+            fees: pos.fees * sale_rate,
+            income: pos.income * sale_rate,
+            currency: Some(self.base.clone()),
+            ..pos.clone()
+        }
+    }
+
+    /// Currencies held without a rate for some position's dates, sorted.
+    pub(crate) fn missing(&self, positions: &[Position]) -> Vec<String> {
+        let mut missing: Vec<String> = positions
+            .iter()
+            .filter(|p| {
+                let currency = self.currency_of(p);
+                self.rate(currency, p.purchase_date).is_none()
+                    || self.rate(currency, p.sale_date).is_none()
+            })
+            .map(|p| self.currency_of(p).to_ascii_uppercase())
+            .collect();
+        missing.sort();
+        missing.dedup();
+        missing
+    }
+}
+
+/// Formats an amount with the currency's symbol and usual decimal places,
+/// e.g. `-£12.50`, `¥1200`, `C$3.10` or `245.00p`.
+pub(crate) fn format_money(value: f64, currency: &str) -> String {
+    let code = currency.to_ascii_uppercase();
+    let decimals = match code.as_str() {
+        "JPY" | "KRW" | "HUF" | "ISK" | "CLP" => 0,
+        _ => 2,
+    };
+    let sign = if value < 0.0 { "-" } else { "" };
+    let amount = format!("{:.*}", decimals, value.abs());
+    let prefix = match code.as_str() {
+        "USD" => "$",
+        "CAD" => "C$",
+        "AUD" => "A$",
+        "NZD" => "NZ$",
+        "HKD" => "HK$",
+        "SGD" => "S$",
+        "EUR" => "€",
+        "GBP" => "£",
+        "JPY" => "¥",
+        "CNY" => "CN¥",
+        "KRW" => "₩",
+        "INR" => "₹",
+        "CHF" => "CHF ",
+        "GBX" => return format!("{sign}{amount}p"),
+        _ => return format!("{sign}{amount} {code}"),
+    };
+    format!("{sign}{prefix}{amount}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATES: &str = "date,currency,rate
+2026-01-02,GBP,1.25
+2026-01-05,GBP,1.26
+2026-03-02,GBP,1.30
+2026-01-05,CAD,0.73
+";
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, m, d).unwrap()
+    }
+
+    #[test]
+    fn uses_the_latest_rate_on_or_before_the_date() {
+        let fx = FxRates::parse(RATES, "usd");
+        assert_eq!(fx.rate("USD", day(1, 1)), Some(1.0));
+        assert_eq!(fx.rate("gbp", day(1, 4)), Some(1.25));
+        assert_eq!(fx.rate("GBP", day(1, 5)), Some(1.26));
+        assert!((fx.rate("GBX", day(3, 9)).unwrap() - 0.013).abs() < 1e-12);
+        assert_eq!(fx.rate("GBP", day(1, 1)), None);
+        assert_eq!(fx.rate("EUR", day(3, 9)), None);
+    }
+
+    #[test]
+    fn splits_base_pnl_into_price_and_fx() {
+        let fx = FxRates::parse(RATES, "USD");
+        let pos = Position {
+            ticker: "VOD".into(),
+            cost_per_share: 10.0,
+            quantity: 100.0,
+            sale_price: 11.0,
+            purchase_date: day(1, 5),
+            sale_date: day(3, 2),
+            currency: Some("GBP".into()),
+            ..Position::default()
+        };
+        let fx_pnl = fx.breakdown(&pos).unwrap();
+        // Bought £1000 at 1.26 = $1260, sold £1100 at 1.30 = $1430.
+        assert!((fx_pnl.pnl - 170.0).abs() < 1e-9);
+        assert!((fx_pnl.price_pnl - 126.0).abs() < 1e-9);
+        assert!((fx_pnl.fx_pnl - 44.0).abs() < 1e-9);
+        assert!((fx.to_base(&pos).roi_value() - fx_pnl.pnl).abs() < 1e-9);
+        assert_eq!(fx.missing(std::slice::from_ref(&pos)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn formats_with_symbols_and_decimals() {
+        assert_eq!(format_money(1234.5, "USD"), "$1234.50");
+        assert_eq!(format_money(-12.5, "GBP"), "-£12.50");
+        assert_eq!(format_money(1200.4, "JPY"), "¥1200");
+        assert_eq!(format_money(245.0, "GBX"), "245.00p");
+        assert_eq!(format_money(3.1, "SEK"), "3.10 SEK");
+    }
+}
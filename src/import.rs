@@ -0,0 +1,1067 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+};
+
+use chrono::NaiveDate;
+use csv::Trim;
+use serde::{Deserialize, Serialize};
+
+use crate::brokers::{detect_broker, parse_broker_records};
+use crate::dividends::{Dividend, DividendKind};
+use crate::journal::parse_tags;
+use crate::options::{OptionContract, parse_symbol};
+use crate::{Position, parse_account, parse_currency, parse_date, parse_f64};
+
+/// Column indices for the fields a position is built from.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct ColumnMap {
+    pub(crate) ticker: usize,
+    pub(crate) cost: usize,
+    pub(crate) qty: usize,
+    pub(crate) sale_price: usize,
+    pub(crate) buy_date: usize,
+    pub(crate) sale_date: usize,
+    #[serde(default)]
+    pub(crate) lot_id: Option<usize>,
+    #[serde(default)]
+    pub(crate) extras: ExtraColumns,
+}
+
+/// Optional columns beyond the six a position needs, as written by
+/// `roi export`.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct ExtraColumns {
+    pub(crate) fees: Option<usize>,
+    pub(crate) currency: Option<usize>,
+    pub(crate) multiplier: Option<usize>,
+    pub(crate) account: Option<usize>,
+    pub(crate) tags: Option<usize>,
+    pub(crate) notes: Option<usize>,
+    pub(crate) stop_price: Option<usize>,
+    pub(crate) target_price: Option<usize>,
+}
+
+impl ExtraColumns {
+    /// Field labels paired with the columns that are present.
+    fn entries(&self) -> Vec<(&'static str, usize)> {
+        [
+            ("Fees", self.fees),
+            ("Currency", self.currency),
+            ("Multiplier", self.multiplier),
+            ("Account", self.account),
+            ("Tags", self.tags),
+            ("Notes", self.notes),
+            ("Stop price", self.stop_price),
+            ("Target price", self.target_price),
+        ]
+        .into_iter()
+        .filter_map(|(label, idx)| Some((label, idx?)))
+        .collect()
+    }
+
+    /// Fills in the fields whose column is present and not blank.
+    fn apply<'a>(
+        &self,
+        position: &mut Position,
+        text: impl Fn(usize) -> &'a str,
+        amount: impl Fn(usize, &str) -> Result<f64, String>,
+    ) -> Result<(), String> {
+        let present = |col: Option<usize>| col.filter(|&i| !text(i).trim().is_empty());
+        if let Some(i) = present(self.fees) {
+            position.fees = amount(i, "fees")?;
+        }
+        if let Some(i) = present(self.currency) {
+            position.currency = parse_currency(text(i))?;
+        }
+        if let Some(i) = present(self.multiplier) {
+            position.multiplier = Some(amount(i, "multiplier")?)
+                .filter(|m| *m > 0.0)
+                .ok_or("Multiplier must be positive")?;
+        }
+        if let Some(i) = present(self.account) {
+            position.account = parse_account(text(i));
+        }
+        if let Some(i) = present(self.tags) {
+            position.tags = parse_tags(text(i));
+        }
+        if let Some(i) = present(self.notes) {
+            position.notes = text(i).to_string();
+        }
+        if let Some(i) = present(self.stop_price) {
+            position.stop_price = Some(amount(i, "stop price")?);
+        }
+        if let Some(i) = present(self.target_price) {
+            position.target_price = Some(amount(i, "target price")?);
+        }
+        Ok(())
+    }
+}
+
+impl ColumnMap {
+    /// Field labels paired with their column index, in `Position` order.
+    pub(crate) fn entries(&self) -> [(&'static str, usize); 6] {
+        [
+            ("Ticker", self.ticker),
+            ("Cost/share", self.cost),
+            ("Quantity", self.qty),
+            ("Sale price", self.sale_price),
+            ("Purchase date", self.buy_date),
+            ("Sale date", self.sale_date),
+        ]
+    }
+}
+
+/// How date and number cells are written in a file.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ValueFormats {
+    /// chrono format string; `None` accepts `YYYY-MM-DD` or `MM/DD/YYYY`.
+    #[serde(default)]
+    pub(crate) date_format: Option<String>,
+    /// `1.234,56` style numbers.
+    #[serde(default)]
+    pub(crate) decimal_comma: bool,
+}
+
+/// A saved column mapping, applied to any file whose header row has the
+/// same signature.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct ImportProfile {
+    pub(crate) name: String,
+    pub(crate) signature: String,
+    pub(crate) columns: ColumnMap,
+    #[serde(default)]
+    pub(crate) formats: ValueFormats,
+}
+
+/// Normalized header names, used to recognize the same export layout again.
+pub(crate) fn header_signature(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|f| sanitize_header(f))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// The header row that was detected and how its columns were mapped.
+#[derive(Clone, Debug)]
+pub(crate) struct DetectedMapping {
+    pub(crate) line: usize,
+    pub(crate) headers: Vec<String>,
+    /// Field labels paired with the column each was read from.
+    pub(crate) fields: Vec<(&'static str, usize)>,
+    pub(crate) formats: ValueFormats,
+    /// Name of the saved or built-in profile that matched, if any.
+    pub(crate) profile: Option<String>,
+}
+
+impl DetectedMapping {
+    pub(crate) fn from_columns(
+        line: usize,
+        headers: &[String],
+        columns: &ColumnMap,
+        formats: ValueFormats,
+        profile: Option<String>,
+    ) -> Self {
+        let lot = columns.lot_id.map(|idx| ("Lot id", idx));
+        Self {
+            line,
+            headers: headers.to_vec(),
+            fields: columns
+                .entries()
+                .into_iter()
+                .chain(lot)
+                .chain(columns.extras.entries())
+                .collect(),
+            formats,
+            profile,
+        }
+    }
+
+    pub(crate) fn describe(&self) -> String {
+        let mut columns = self
+            .fields
+            .iter()
+            .map(|&(label, idx)| {
+                let header = self.headers.get(idx).map(|s| s.as_str()).unwrap_or("?");
+                format!("{label}={header} (col {})", idx + 1)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        if let Some(fmt) = &self.formats.date_format {
+            columns.push_str(&format!(", dates {fmt}"));
+        }
+        if self.formats.decimal_comma {
+            columns.push_str(", decimal comma");
+        }
+        match &self.profile {
+            Some(name) => format!("profile \"{name}\": {columns}"),
+            None => columns,
+        }
+    }
+}
+
+/// How an imported row relates to the positions already stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum MatchStatus {
+    New,
+    /// Identical fingerprint to the existing position at this index.
+    Duplicate(usize),
+    /// Same lot as the existing position at this index, with different values.
+    Changed(usize),
+}
+
+impl MatchStatus {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            MatchStatus::New => "new",
+            MatchStatus::Duplicate(_) => "duplicate",
+            MatchStatus::Changed(_) => "changed",
+        }
+    }
+}
+
+/// What to do with rows that match an existing position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum DuplicatePolicy {
+    Skip,
+    Replace,
+    ImportAnyway,
+}
+
+impl DuplicatePolicy {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            DuplicatePolicy::Skip => "skip",
+            DuplicatePolicy::Replace => "replace",
+            DuplicatePolicy::ImportAnyway => "import anyway",
+        }
+    }
+
+    pub(crate) fn next(self) -> Self {
+        match self {
+            DuplicatePolicy::Skip => DuplicatePolicy::Replace,
+            DuplicatePolicy::Replace => DuplicatePolicy::ImportAnyway,
+            DuplicatePolicy::ImportAnyway => DuplicatePolicy::Skip,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct ImportRow {
+    pub(crate) line: usize,
+    pub(crate) position: Position,
+    pub(crate) selected: bool,
+    pub(crate) status: MatchStatus,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct SkippedRow {
+    pub(crate) line: usize,
+    pub(crate) reason: String,
+}
+
+/// Everything the parser found in a file, before anything is committed.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ImportReport {
+    pub(crate) rows: Vec<ImportRow>,
+    pub(crate) skipped: Vec<SkippedRow>,
+    pub(crate) mapping: Option<DetectedMapping>,
+    /// Statement transactions behind the rows, for formats that carry them.
+    pub(crate) transactions: Vec<Transaction>,
+}
+
+impl ImportReport {
+    /// One-line tally of the statement transactions, if there are any.
+    pub(crate) fn transaction_summary(&self) -> Option<String> {
+        if self.transactions.is_empty() {
+            return None;
+        }
+        let (mut buys, mut sells, mut income, mut splits) = (0, 0, 0.0, 0);
+        for tx in &self.transactions {
+            match tx {
+                Transaction::Trade(t) if t.side == Side::Buy => buys += 1,
+                Transaction::Trade(_) => sells += 1,
+                Transaction::Income { amount, .. } => income += amount,
+                Transaction::Split { .. } => splits += 1,
+            }
+        }
+        Some(format!(
+            "Statement: {buys} buys, {sells} sells, {splits} splits, {income:.2} income"
+        ))
+    }
+
+    /// Income entries, which never become rows. Dividends and distributions
+    /// go to the dividend ledger; other income is only listed.
+    pub(crate) fn income_notes(&self) -> Vec<(usize, String)> {
+        self.transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Income {
+                    line,
+                    ticker,
+                    date,
+                    kind,
+                    amount,
+                    ..
+                } => {
+                    let outcome = match DividendKind::from_code(kind) {
+                        Some(_) => "for the dividend ledger",
+                        None => "not tracked",
+                    };
+                    Some((
+                        *line,
+                        format!("{kind} {amount:.2} from {ticker} on {date}, {outcome}"),
+                    ))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Dividend and distribution payments among the transactions.
+    pub(crate) fn dividends(&self) -> Vec<Dividend> {
+        self.transactions
+            .iter()
+            .filter_map(|tx| match tx {
+                Transaction::Income {
+                    ticker,
+                    date,
+                    ex_date,
+                    kind,
+                    amount,
+                    ..
+                } => Some(Dividend {
+                    ticker: ticker.clone(),
+                    ex_date: *ex_date,
+                    pay_date: *date,
+                    amount: amount.abs(),
+                    kind: DividendKind::from_code(kind)?,
+                    account: None,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub(crate) fn selected_count(&self) -> usize {
+        self.rows.iter().filter(|r| r.selected).count()
+    }
+
+    /// Marks each row as new, duplicate or changed relative to `existing`.
+    /// Every existing position is matched at most once, so a file holding two
+    /// identical lots only flags as many duplicates as are already stored.
+    pub(crate) fn classify(&mut self, existing: &[Position]) {
+        let mut used = vec![false; existing.len()];
+        for row in &mut self.rows {
+            let fingerprint = row.position.fingerprint();
+            let identity = row.position.identity_key();
+            let exact = existing
+                .iter()
+                .enumerate()
+                .position(|(i, p)| !used[i] && p.fingerprint() == fingerprint);
+            let same_lot = || {
+                existing
+                    .iter()
+                    .enumerate()
+                    .position(|(i, p)| !used[i] && p.identity_key() == identity)
+            };
+            row.status = match exact {
+                Some(i) => MatchStatus::Duplicate(i),
+                None => match same_lot() {
+                    Some(i) => MatchStatus::Changed(i),
+                    None => MatchStatus::New,
+                },
+            };
+            if let MatchStatus::Duplicate(i) | MatchStatus::Changed(i) = row.status {
+                used[i] = true;
+            }
+        }
+    }
+
+    /// Counts of selected rows by status: (new, duplicate, changed).
+    pub(crate) fn status_counts(&self) -> (usize, usize, usize) {
+        let mut counts = (0, 0, 0);
+        for row in self.rows.iter().filter(|r| r.selected) {
+            match row.status {
+                MatchStatus::New => counts.0 += 1,
+                MatchStatus::Duplicate(_) => counts.1 += 1,
+                MatchStatus::Changed(_) => counts.2 += 1,
+            }
+        }
+        counts
+    }
+}
+
+pub(crate) fn sanitize_header(s: &str) -> String {
+    s.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+fn detect_header(parts: &[String]) -> Option<ColumnMap> {
+    let mut t = None;
+    let mut cost = None;
+    let mut qty = None;
+    let mut sale = None;
+    let mut buy_d = None;
+    let mut sale_d = None;
+    let mut lot = None;
+    let mut extras = ExtraColumns::default();
+    let mut date_cols: Vec<usize> = Vec::new();
+
+    for (i, raw) in parts.iter().enumerate() {
+        let h = sanitize_header(raw);
+        match h.as_str() {
+            "symbol" | "ticker" => t = Some(i),
+            "qty" | "qtynumber" | "qtyshare" | "quantity" | "qtyshares" => qty = Some(i),
+            "costshare" | "costpershare" => cost = Some(i),
+            "priceshare" | "pricepershare" | "saleprice" | "sellprice" => sale = Some(i),
+            "lotid" | "lotnumber" => lot = Some(i),
+            "fees" => extras.fees = Some(i),
+            "currency" => extras.currency = Some(i),
+            "multiplier" => extras.multiplier = Some(i),
+            "account" => extras.account = Some(i),
+            "tags" => extras.tags = Some(i),
+            "notes" => extras.notes = Some(i),
+            "stopprice" => extras.stop_price = Some(i),
+            "targetprice" => extras.target_price = Some(i),
+            "dateadded" | "purchasedate" | "buydate" => buy_d = Some(i),
+            "date" | "saledate" | "selldate" => date_cols.push(i),
+            _ => {}
+        }
+    }
+
+    if buy_d.is_none()
+        && let Some(&first_date) = date_cols.first()
+    {
+        buy_d = Some(first_date);
+    }
+    if sale_d.is_none() {
+        if let Some(second_date) = date_cols.get(1) {
+            sale_d = Some(*second_date);
+        } else if let Some(&first_date) = date_cols.first() {
+            sale_d = Some(first_date);
+        }
+    }
+
+    match (t, cost, qty, sale, buy_d, sale_d) {
+        (Some(t), Some(c), Some(q), Some(s), Some(bd), Some(sd)) => Some(ColumnMap {
+            ticker: t,
+            cost: c,
+            qty: q,
+            sale_price: s,
+            buy_date: bd,
+            sale_date: sd,
+            lot_id: lot,
+            extras,
+        }),
+        _ => None,
+    }
+}
+
+/// Reads a CSV file into `(line number, fields)` records.
+pub(crate) fn read_csv_records(path: &str) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    let mut rdr = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .trim(Trim::All)
+        .flexible(true)
+        .from_reader(data.as_bytes());
+
+    let mut records = Vec::new();
+    for (idx, result) in rdr.records().enumerate() {
+        let record = result.map_err(|e| format!("Line {}: {e}", idx + 1))?;
+        let line_no = record
+            .position()
+            .map(|p| p.line() as usize)
+            .unwrap_or(idx + 1);
+        if record.is_empty() {
+            continue;
+        }
+        records.push((line_no, record.iter().map(|s| s.to_string()).collect()));
+    }
+    Ok(records)
+}
+
+/// A typed value from a spreadsheet cell, used instead of parsing its text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum NativeCell {
+    Number(f64),
+    Date(NaiveDate),
+}
+
+/// Native cell values keyed by (line, column). CSV files have none.
+pub(crate) type NativeCells = HashMap<(usize, usize), NativeCell>;
+
+/// Parses a file's records using, in order of preference, a saved profile
+/// whose header signature appears in the file, a built-in broker profile, or
+/// the generic header detection. `today` decides which open options in a
+/// broker's activity have expired.
+pub(crate) fn parse_import_records(
+    records: &[(usize, Vec<String>)],
+    native: &NativeCells,
+    profiles: &[ImportProfile],
+    today: NaiveDate,
+) -> ImportReport {
+    let has_saved_profile = records.iter().any(|(_, fields)| {
+        let signature = header_signature(fields);
+        profiles.iter().any(|p| p.signature == signature)
+    });
+    if !has_saved_profile && let Some((broker, header_row)) = detect_broker(records) {
+        return parse_broker_records(broker, records, header_row, today);
+    }
+    parse_position_records(records, native, profiles)
+}
+
+/// Parses position rows, taking the column layout from the first header row
+/// that matches a saved profile or the built-in header names. The report has
+/// no mapping when no header was recognized.
+pub(crate) fn parse_position_records(
+    records: &[(usize, Vec<String>)],
+    native: &NativeCells,
+    profiles: &[ImportProfile],
+) -> ImportReport {
+    // Brokerage exports carry several tables; only the G&L details one holds lots.
+    let has_details_section = records.iter().any(|(_, fields)| is_details_marker(fields));
+
+    let mut report = ImportReport::default();
+    let mut header_idx: Option<ColumnMap> = None;
+    let mut formats = ValueFormats::default();
+    let mut in_details_section = false;
+    let mut current_ticker: Option<(String, Option<OptionContract>)> = None;
+
+    for (line_no, fields) in records {
+        let line_no = *line_no;
+        if is_details_marker(fields) {
+            in_details_section = true;
+            header_idx = None;
+            continue;
+        }
+
+        // Skip anything before we reach the TAXABLE G&L DETAILS table.
+        if has_details_section && !in_details_section {
+            continue;
+        }
+
+        // Skip summary/total lines but keep headers that include the word "Total"
+        if fields.len() == 1 {
+            let first = fields[0].trim().to_ascii_lowercase();
+            if first.contains("total") || first.contains("subtotal") {
+                continue;
+            }
+        }
+        if let Some(first) = fields.first() {
+            let first_lower = first.trim().to_ascii_lowercase();
+            if first_lower == "total" || first_lower == "subtotal" {
+                continue;
+            }
+        }
+
+        let Some(h) = header_idx else {
+            let signature = header_signature(fields);
+            let detected = match profiles.iter().find(|p| p.signature == signature) {
+                Some(p) => Some((p.columns, p.formats.clone(), Some(p.name.clone()))),
+                None => detect_header(fields).map(|h| (h, ValueFormats::default(), None)),
+            };
+            if let Some((h, fmt, profile)) = detected {
+                header_idx = Some(h);
+                formats = fmt.clone();
+                report.mapping = Some(DetectedMapping::from_columns(
+                    line_no, fields, &h, fmt, profile,
+                ));
+            }
+            // Not a header row; ignore until we find one.
+            continue;
+        };
+
+        let get = |i: usize| fields.get(i).map(|s| s.as_str()).unwrap_or("");
+        let skip = |report: &mut ImportReport, reason: String| {
+            report.skipped.push(SkippedRow {
+                line: line_no,
+                reason,
+            });
+        };
+
+        let raw_ticker = get(h.ticker).trim();
+        // Update current ticker when we see a non-sell summary row, even if numbers are missing.
+        if !raw_ticker.is_empty()
+            && raw_ticker != "--"
+            && !raw_ticker.to_ascii_lowercase().starts_with("sell")
+        {
+            match parse_symbol(raw_ticker) {
+                Ok(parsed) => current_ticker = Some(parsed),
+                Err(e) => {
+                    skip(&mut report, e);
+                    continue;
+                }
+            }
+        }
+
+        let missing: Vec<&str> = [
+            ("cost/share", h.cost),
+            ("quantity", h.qty),
+            ("sale price", h.sale_price),
+            ("purchase date", h.buy_date),
+            ("sale date", h.sale_date),
+        ]
+        .iter()
+        .filter(|(_, i)| {
+            let v = get(*i).trim();
+            v.is_empty() || v == "--"
+        })
+        .map(|(label, _)| *label)
+        .collect();
+        if !missing.is_empty() {
+            let reason = match &current_ticker {
+                Some((t, _)) if !raw_ticker.is_empty() && raw_ticker != "--" => {
+                    format!("{t} summary row, missing {}", missing.join(", "))
+                }
+                _ => format!("missing {}", missing.join(", ")),
+            };
+            skip(&mut report, reason);
+            continue;
+        }
+
+        let Some((ticker, option)) = current_ticker.clone() else {
+            skip(&mut report, "no ticker context before this row".into());
+            continue;
+        };
+
+        let amount = |col: usize, label: &str| match native.get(&(line_no, col)) {
+            Some(NativeCell::Number(n)) => Ok(*n),
+            _ => parse_amount(get(col), &formats, label),
+        };
+        let day = |col: usize, label: &str| match native.get(&(line_no, col)) {
+            Some(NativeCell::Date(d)) => Ok(*d),
+            _ => parse_day(get(col), &formats, label),
+        };
+        let built = build_position(ticker, &h, amount, day).and_then(|mut position| {
+            position.set_symbol(position.ticker.clone(), option);
+            position.lot_id = h
+                .lot_id
+                .map(|i| get(i).trim())
+                .filter(|v| !v.is_empty() && *v != "--")
+                .map(str::to_string);
+            h.extras.apply(&mut position, get, amount)?;
+            Ok(position)
+        });
+        match built {
+            Ok(position) => {
+                report.rows.push(ImportRow {
+                    line: line_no,
+                    position,
+                    selected: true,
+                    status: MatchStatus::New,
+                });
+            }
+            Err(e) => skip(&mut report, e),
+        }
+    }
+
+    report
+}
+
+fn is_details_marker(fields: &[String]) -> bool {
+    fields
+        .join(" ")
+        .to_ascii_lowercase()
+        .contains("taxable g&l details")
+}
+
+fn build_position(
+    ticker: String,
+    columns: &ColumnMap,
+    amount: impl Fn(usize, &str) -> Result<f64, String>,
+    day: impl Fn(usize, &str) -> Result<NaiveDate, String>,
+) -> Result<Position, String> {
+    let cost = amount(columns.cost, "cost/share")?;
+    let qty = amount(columns.qty, "quantity")?;
+    let sale_price = amount(columns.sale_price, "sale price")?;
+    let purchase_date = day(columns.buy_date, "purchase date")?;
+    let sale_date = day(columns.sale_date, "sale date")?;
+
+    if sale_date < purchase_date {
+        return Err("Sale date cannot be before purchase date".into());
+    }
+
+    Ok(Position {
+        ticker,
+        cost_per_share: cost,
+        quantity: qty,
+        sale_price,
+        purchase_date,
+        sale_date,
+        ..Default::default()
+    })
+}
+
+fn parse_amount(raw: &str, formats: &ValueFormats, label: &str) -> Result<f64, String> {
+    if !formats.decimal_comma {
+        return parse_f64(raw, label);
+    }
+    let converted: String = raw
+        .chars()
+        .filter(|c| *c != '.')
+        .map(|c| if c == ',' { '.' } else { c })
+        .collect();
+    parse_f64(&converted, label)
+}
+
+fn parse_day(raw: &str, formats: &ValueFormats, label: &str) -> Result<NaiveDate, String> {
+    match &formats.date_format {
+        Some(fmt) => NaiveDate::parse_from_str(raw.trim(), fmt)
+            .map_err(|_| format!("Invalid {label}, expected {fmt}")),
+        None => parse_date(raw, label),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            // BTO/STC open and close a long option position.
+            "buy" | "bot" | "bought" | "b" | "bto" => Some(Side::Buy),
+            "sell" | "sld" | "sold" | "s" | "stc" => Some(Side::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// A single fill from an activity-style export.
+#[derive(Clone, Debug)]
+pub(crate) struct Trade {
+    pub(crate) line: usize,
+    /// The underlying for options.
+    pub(crate) ticker: String,
+    pub(crate) option: Option<OptionContract>,
+    pub(crate) date: NaiveDate,
+    pub(crate) side: Side,
+    pub(crate) quantity: f64,
+    pub(crate) price: f64,
+    /// Commission and fees paid on the whole fill.
+    pub(crate) fees: f64,
+    /// Broker transaction id, when the export has one.
+    pub(crate) id: Option<String>,
+}
+
+impl Trade {
+    /// The ticker, or the option contract traded.
+    pub(crate) fn label(&self) -> String {
+        match &self.option {
+            Some(contract) => contract.label(&self.ticker),
+            None => self.ticker.clone(),
+        }
+    }
+}
+
+/// An entry from a statement's transaction list.
+#[derive(Clone, Debug)]
+pub(crate) enum Transaction {
+    Trade(Trade),
+    /// Dividend, interest or capital-gain distribution.
+    Income {
+        line: usize,
+        ticker: String,
+        date: NaiveDate,
+        /// Ex-date (or record date) of a distribution, when the file gives it.
+        ex_date: Option<NaiveDate>,
+        kind: String,
+        amount: f64,
+    },
+    /// New shares per old share, e.g. 2.0 for a 2-for-1 split.
+    Split {
+        line: usize,
+        ticker: String,
+        date: NaiveDate,
+        ratio: f64,
+    },
+}
+
+/// Pairs sells with earlier buys of the same ticker, first-in first-out, and
+/// adds one row per closed lot. Options still open that expired before
+/// `today` close at zero; unmatched sells and other lots still open at the
+/// end of the file are reported as skipped.
+pub(crate) fn match_lots_fifo(mut trades: Vec<Trade>, today: NaiveDate, report: &mut ImportReport) {
+    const EPS: f64 = 1e-9;
+    // Same-day buys go first so intraday round trips pair up.
+    trades.sort_by(|a, b| a.date.cmp(&b.date).then(a.side.cmp(&b.side)));
+
+    let mut open: Vec<VecDeque<Trade>> = Vec::new();
+    for trade in trades {
+        let lots = match open
+            .iter()
+            .position(|lots| lots[0].ticker == trade.ticker && lots[0].option == trade.option)
+        {
+            Some(i) => &mut open[i],
+            None if trade.side == Side::Buy => {
+                open.push(VecDeque::new());
+                open.last_mut().expect("just pushed")
+            }
+            None => {
+                report.skipped.push(SkippedRow {
+                    line: trade.line,
+                    reason: format!(
+                        "sell of {} {} has no matching buy in this file",
+                        trade.quantity,
+                        trade.label()
+                    ),
+                });
+                continue;
+            }
+        };
+        if trade.side == Side::Buy {
+            lots.push_back(trade);
+            continue;
+        }
+
+        let mut remaining = trade.quantity;
+        while remaining > EPS {
+            let Some(lot) = lots.front_mut() else {
+                break;
+            };
+            let qty = remaining.min(lot.quantity);
+            // Each side's fees are shared pro rata across the lots it touches.
+            let buy_fees = lot.fees * qty / lot.quantity;
+            let sell_fees = trade.fees * qty / trade.quantity;
+            let lot_id = match (&lot.id, &trade.id) {
+                (Some(buy), Some(sell)) => Some(format!("{buy}/{sell}")),
+                _ => None,
+            };
+            report.rows.push(closed_lot(
+                lot,
+                qty,
+                trade.line,
+                trade.price,
+                trade.date,
+                lot_id,
+                buy_fees + sell_fees,
+            ));
+            lot.fees -= buy_fees;
+            lot.quantity -= qty;
+            remaining -= qty;
+            if lot.quantity <= EPS {
+                lots.pop_front();
+            }
+        }
+        if remaining > EPS {
+            report.skipped.push(SkippedRow {
+                line: trade.line,
+                reason: format!(
+                    "sell of {remaining} {} has no matching buy in this file",
+                    trade.label()
+                ),
+            });
+        }
+        open.retain(|lots| !lots.is_empty());
+    }
+
+    // Options still open after their expiry expired worthless.
+    for lot in open.into_iter().flatten() {
+        match lot.option {
+            Some(contract) if contract.expiry < today => {
+                let row = closed_lot(
+                    &lot,
+                    lot.quantity,
+                    lot.line,
+                    0.0,
+                    contract.expiry,
+                    None,
+                    lot.fees,
+                );
+                report.rows.push(row);
+            }
+            _ => report.skipped.push(SkippedRow {
+                line: lot.line,
+                reason: format!("{} {} still open, not imported", lot.quantity, lot.label()),
+            }),
+        }
+    }
+    report.skipped.sort_by_key(|s| s.line);
+}
+
+/// The part of an opening trade closed at `price` on `date`.
+fn closed_lot(
+    lot: &Trade,
+    quantity: f64,
+    line: usize,
+    price: f64,
+    date: NaiveDate,
+    lot_id: Option<String>,
+    fees: f64,
+) -> ImportRow {
+    let mut position = Position {
+        cost_per_share: lot.price,
+        quantity,
+        sale_price: price,
+        purchase_date: lot.date,
+        sale_date: date,
+        lot_id,
+        fees,
+        ..Default::default()
+    };
+    position.set_symbol(lot.ticker.clone(), lot.option);
+    ImportRow {
+        line,
+        position,
+        selected: true,
+        status: MatchStatus::New,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(csv: &str) -> Vec<(usize, Vec<String>)> {
+        csv.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(i, line)| (i + 1, line.split(',').map(str::to_string).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn preview_reports_rows_and_skip_reasons() {
+        let report = parse_position_records(
+            &records(
+                "Account summary\n\
+                 Symbol,Qty (#),Cost/Share,Sale Price,Purchase Date,Sale Date,Lot Id\n\
+                 KO,10,50,55,2026-01-05,2026-02-05,A1\n\
+                 PEP,5,,180,2026-01-05,2026-02-05,\n\
+                 XOM,5,100,110,2026-03-05,2026-02-05,\n\
+                 MSFT,abc,300,310,2026-01-05,2026-02-05,\n\
+                 Total,,,,,,\n",
+            ),
+            &NativeCells::new(),
+            &[],
+        );
+        let mapping = report.mapping.as_ref().unwrap();
+        assert_eq!(mapping.line, 2);
+        assert_eq!(
+            mapping.fields,
+            vec![
+                ("Ticker", 0),
+                ("Cost/share", 2),
+                ("Quantity", 1),
+                ("Sale price", 3),
+                ("Purchase date", 4),
+                ("Sale date", 5),
+                ("Lot id", 6),
+            ]
+        );
+
+        assert_eq!(report.rows.len(), 1);
+        let row = &report.rows[0];
+        assert_eq!(row.line, 3);
+        assert_eq!(row.position.ticker, "KO");
+        assert_eq!(row.position.cost_per_share, 50.0);
+        assert_eq!(row.position.lot_id.as_deref(), Some("A1"));
+
+        let skipped: Vec<(usize, &str)> = report
+            .skipped
+            .iter()
+            .map(|s| (s.line, s.reason.as_str()))
+            .collect();
+        assert_eq!(
+            skipped,
+            vec![
+                (4, "PEP summary row, missing cost/share"),
+                (5, "Sale date cannot be before purchase date"),
+                (6, "Invalid quantity"),
+            ]
+        );
+    }
+
+    fn lot(ticker: &str, sale_price: f64, lot_id: Option<&str>) -> Position {
+        Position {
+            ticker: ticker.into(),
+            cost_per_share: 50.0,
+            quantity: 10.0,
+            sale_price,
+            purchase_date: NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            sale_date: NaiveDate::from_ymd_opt(2026, 2, 5).unwrap(),
+            lot_id: lot_id.map(str::to_string),
+            ..Position::default()
+        }
+    }
+
+    #[test]
+    fn rows_are_classified_against_stored_positions() {
+        let existing = vec![
+            lot("KO", 55.0, None),
+            lot("PEP", 180.0, Some("L1")),
+            lot("KO", 55.0, None),
+        ];
+        let mut report = ImportReport::default();
+        for (line, position) in [
+            lot("KO", 55.0, None),
+            lot("KO", 55.0, None),
+            // A third identical lot has nothing left to match.
+            lot("KO", 55.0, None),
+            lot("PEP", 181.5, Some("L1")),
+            lot("XOM", 110.0, None),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            report.rows.push(ImportRow {
+                line,
+                position,
+                selected: true,
+                status: MatchStatus::New,
+            });
+        }
+        report.classify(&existing);
+        let statuses: Vec<MatchStatus> = report.rows.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                MatchStatus::Duplicate(0),
+                MatchStatus::Duplicate(2),
+                MatchStatus::New,
+                MatchStatus::Changed(1),
+                MatchStatus::New,
+            ]
+        );
+        assert_eq!(report.status_counts(), (2, 2, 1));
+    }
+
+    #[test]
+    fn replacing_a_changed_lot_keeps_the_journal() {
+        let mut stored = Position {
+            tags: vec!["breakout".into()],
+            notes: "held through earnings".into(),
+            account: Some("IRA".into()),
+            stop_price: Some(48.0),
+            ..lot("PEP", 180.0, Some("L1"))
+        };
+        let imported = Position {
+            fees: 1.25,
+            account: None,
+            ..lot("PEP", 181.5, Some("L1"))
+        };
+        stored.update_from_import(imported);
+        assert_eq!(stored.sale_price, 181.5);
+        assert_eq!(stored.fees, 1.25);
+        assert_eq!(stored.tags, vec!["breakout".to_string()]);
+        assert_eq!(stored.notes, "held through earnings");
+        assert_eq!(stored.account.as_deref(), Some("IRA"));
+        assert_eq!(stored.stop_price, Some(48.0));
+    }
+
+    #[test]
+    fn total_basis_is_not_mistaken_for_cost_per_share() {
+        let fields: Vec<String> = ["Symbol", "Quantity", "Cost", "Sale Price", "Date", "Date"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(detect_header(&fields).is_none());
+    }
+}
@@ -0,0 +1,427 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+use crate::{DATE_FMT, parse_date_any, parse_number};
+
+/// What a field accepts. Keys and pasted text that don't fit are rejected
+/// as they are typed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InputKind {
+    Text,
+    Number,
+    Date,
+}
+
+impl InputKind {
+    fn accepts(self, c: char) -> bool {
+        match self {
+            InputKind::Text => !c.is_control(),
+            InputKind::Number => c.is_ascii_digit() || matches!(c, '.' | ',' | '-' | '$'),
+            InputKind::Date => c.is_ascii_digit() || matches!(c, '-' | '/'),
+        }
+    }
+}
+
+/// A single-line editor with a cursor, used by the forms.
+#[derive(Clone, Debug)]
+pub(crate) struct TextInput {
+    value: String,
+    /// Cursor position in characters, `0..=len`.
+    cursor: usize,
+    kind: InputKind,
+    /// The last key that was refused, shown until the next edit.
+    rejected: Option<char>,
+}
+
+impl TextInput {
+    pub(crate) fn new(kind: InputKind) -> Self {
+        Self {
+            value: String::new(),
+            cursor: 0,
+            kind,
+            rejected: None,
+        }
+    }
+
+    pub(crate) fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.value.is_empty()
+    }
+
+    /// Replaces the text and puts the cursor at the end.
+    pub(crate) fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.chars().count();
+        self.rejected = None;
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.value
+            .char_indices()
+            .nth(cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.value.len())
+    }
+
+    fn len(&self) -> usize {
+        self.value.chars().count()
+    }
+
+    /// Inserts pasted text at the cursor, dropping line breaks and any
+    /// characters the field doesn't accept.
+    pub(crate) fn insert_str(&mut self, text: &str) {
+        self.rejected = None;
+        for c in text.chars().filter(|c| *c != '\n' && *c != '\r') {
+            if self.kind.accepts(c) {
+                self.insert(c);
+            } else {
+                self.rejected = Some(c);
+            }
+        }
+    }
+
+    fn insert(&mut self, c: char) {
+        let at = self.byte_index(self.cursor);
+        self.value.insert(at, c);
+        self.cursor += 1;
+    }
+
+    /// Removes the characters between two cursor positions.
+    fn remove_range(&mut self, from: usize, to: usize) {
+        let (start, end) = (self.byte_index(from), self.byte_index(to));
+        self.value.replace_range(start..end, "");
+        self.cursor = from;
+    }
+
+    /// Applies an editing key. Returns `false` for keys the input doesn't
+    /// handle, so the form can use them.
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        self.rejected = None;
+        match key.code {
+            KeyCode::Char('w') if ctrl => {
+                // Delete back over whitespace, then the word before it.
+                let chars: Vec<char> = self.value.chars().collect();
+                let mut start = self.cursor;
+                while start > 0 && chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                while start > 0 && !chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+                self.remove_range(start, self.cursor);
+            }
+            KeyCode::Char('u') if ctrl => self.remove_range(0, self.cursor),
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Char(_) if ctrl || key.modifiers.contains(KeyModifiers::ALT) => return false,
+            KeyCode::Char(c) => {
+                if self.kind.accepts(c) {
+                    self.insert(c);
+                } else {
+                    self.rejected = Some(c);
+                }
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.remove_range(self.cursor - 1, self.cursor)
+            }
+            KeyCode::Delete if self.cursor < self.len() => {
+                self.remove_range(self.cursor, self.cursor + 1)
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Feedback for the current text: a rejected key, an unparsable value,
+    /// or the date a date field will be read as.
+    pub(crate) fn feedback(&self) -> Option<Result<String, String>> {
+        if let Some(c) = self.rejected {
+            let expected = match self.kind {
+                InputKind::Number => "numbers",
+                InputKind::Date => "dates (digits, - or /)",
+                InputKind::Text => "text",
+            };
+            return Some(Err(format!("'{c}' not allowed, field takes {expected}")));
+        }
+        if self.value.trim().is_empty() {
+            return None;
+        }
+        match self.kind {
+            InputKind::Text => None,
+            InputKind::Number => match parse_number(&self.value) {
+                Some(_) => None,
+                None => Some(Err("not a number yet".into())),
+            },
+            InputKind::Date => Some(match parse_date_any(&self.value) {
+                Ok(date) => Ok(format!(
+                    "{} ({})",
+                    date.format(DATE_FMT),
+                    date.format("%a %-d %b %Y")
+                )),
+                Err(()) => Err("expected YYYY-MM-DD or MM/DD/YYYY".into()),
+            }),
+        }
+    }
+
+    /// The text as spans, with the cursor drawn as a reversed cell when focused.
+    pub(crate) fn spans(&self, focused: bool, placeholder: &'static str) -> Vec<Span<'_>> {
+        if !focused {
+            return vec![if self.value.is_empty() {
+                Span::styled(placeholder, Style::default().fg(Color::DarkGray))
+            } else {
+                Span::raw(self.value.as_str())
+            }];
+        }
+        let cursor_style = Style::default().add_modifier(Modifier::REVERSED);
+        let at = self.byte_index(self.cursor);
+        let (before, rest) = self.value.split_at(at);
+        let mut spans = vec![Span::raw(before)];
+        match rest.chars().next() {
+            Some(c) => {
+                spans.push(Span::styled(&rest[..c.len_utf8()], cursor_style));
+                spans.push(Span::raw(&rest[c.len_utf8()..]));
+            }
+            None => spans.push(Span::styled(" ", cursor_style)),
+        }
+        if self.value.is_empty() {
+            spans.push(Span::styled(
+                placeholder,
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        spans
+    }
+}
+
+/// A multi-line editor for free text such as trade notes.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TextArea {
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor column in characters.
+    col: usize,
+}
+
+impl TextArea {
+    pub(crate) fn new(text: &str) -> Self {
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        Self { lines, row, col }
+    }
+
+    /// The text with trailing blank lines dropped.
+    pub(crate) fn text(&self) -> String {
+        self.lines.join("\n").trim_end().to_string()
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self, col: usize) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices()
+            .nth(col)
+            .map(|(i, _)| i)
+            .unwrap_or(line.len())
+    }
+
+    fn insert(&mut self, c: char) {
+        if c == '\n' {
+            let at = self.byte_index(self.col);
+            let rest = self.lines[self.row].split_off(at);
+            self.row += 1;
+            self.lines.insert(self.row, rest);
+            self.col = 0;
+        } else if !c.is_control() {
+            let at = self.byte_index(self.col);
+            self.lines[self.row].insert(at, c);
+            self.col += 1;
+        }
+    }
+
+    pub(crate) fn insert_str(&mut self, text: &str) {
+        for c in text.chars().filter(|c| *c != '\r') {
+            self.insert(c);
+        }
+    }
+
+    /// Applies an editing key; Enter starts a new line. Returns `false` for
+    /// keys the editor doesn't handle.
+    pub(crate) fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if key
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+        {
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) => self.insert(c),
+            KeyCode::Enter => self.insert('\n'),
+            KeyCode::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let at = self.byte_index(self.col);
+                self.lines[self.row].remove(at);
+            }
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.col < self.line_len(self.row) => {
+                let at = self.byte_index(self.col);
+                self.lines[self.row].remove(at);
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let line = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Backspace | KeyCode::Delete => {}
+            KeyCode::Left if self.col > 0 => self.col -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            KeyCode::Right if self.col < self.line_len(self.row) => self.col += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Left | KeyCode::Right => {}
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_len(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_len(self.row));
+            }
+            KeyCode::Up | KeyCode::Down => {}
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(self.row),
+            _ => return false,
+        }
+        true
+    }
+
+    /// One `Line` per text line, with the cursor drawn when focused.
+    pub(crate) fn lines(&self, focused: bool) -> Vec<Line<'_>> {
+        let cursor_style = Style::default().add_modifier(Modifier::REVERSED);
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                if !focused || row != self.row {
+                    return Line::from(line.as_str());
+                }
+                let at = self.byte_index(self.col);
+                let (before, rest) = line.split_at(at);
+                let mut spans = vec![Span::raw(before)];
+                match rest.chars().next() {
+                    Some(c) => {
+                        spans.push(Span::styled(&rest[..c.len_utf8()], cursor_style));
+                        spans.push(Span::raw(&rest[c.len_utf8()..]));
+                    }
+                    None => spans.push(Span::styled(" ", cursor_style)),
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(input: &mut TextInput, code: KeyCode) {
+        input.handle_key(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn ctrl(input: &mut TextInput, c: char) {
+        input.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    }
+
+    #[test]
+    fn edits_in_the_middle() {
+        let mut input = TextInput::new(InputKind::Number);
+        input.set_value("12.40");
+        for _ in 0..3 {
+            press(&mut input, KeyCode::Left);
+        }
+        press(&mut input, KeyCode::Backspace);
+        press(&mut input, KeyCode::Char('3'));
+        assert_eq!(input.value(), "13.40");
+        press(&mut input, KeyCode::Delete);
+        assert_eq!(input.value(), "1340");
+        press(&mut input, KeyCode::Home);
+        press(&mut input, KeyCode::Char('9'));
+        assert_eq!(input.value(), "91340");
+    }
+
+    #[test]
+    fn numeric_fields_reject_letters() {
+        let mut input = TextInput::new(InputKind::Number);
+        press(&mut input, KeyCode::Char('4'));
+        press(&mut input, KeyCode::Char('x'));
+        assert_eq!(input.value(), "4");
+        assert!(matches!(input.feedback(), Some(Err(_))));
+        input.insert_str("2.5abc\n");
+        assert_eq!(input.value(), "42.5");
+    }
+
+    #[test]
+    fn ctrl_w_and_ctrl_u_delete_before_cursor() {
+        let mut input = TextInput::new(InputKind::Text);
+        input.set_value("data/my trades.csv");
+        ctrl(&mut input, 'w');
+        assert_eq!(input.value(), "data/my ");
+        ctrl(&mut input, 'w');
+        assert_eq!(input.value(), "");
+        input.set_value("abc def");
+        press(&mut input, KeyCode::Left);
+        ctrl(&mut input, 'u');
+        assert_eq!(input.value(), "f");
+    }
+
+    #[test]
+    fn text_area_splits_and_joins_lines() {
+        let mut area = TextArea::new("entry on breakout");
+        for _ in 0..9 {
+            area.handle_key(KeyEvent::new(KeyCode::Left, KeyModifiers::NONE));
+        }
+        area.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(area.text(), "entry on\n breakout");
+        area.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        assert_eq!(area.text(), "entry on breakout");
+        area.insert_str("\r\nsold into strength\n");
+        assert_eq!(area.text(), "entry on\nsold into strength\n breakout");
+    }
+
+    #[test]
+    fn date_fields_show_the_parsed_date() {
+        let mut input = TextInput::new(InputKind::Date);
+        input.set_value("01/27/2026");
+        let shown = input.feedback().unwrap().unwrap();
+        assert!(shown.starts_with("2026-01-27"), "{shown}");
+        input.set_value("2026-13-01");
+        assert!(input.feedback().unwrap().is_err());
+    }
+}
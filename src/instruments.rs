@@ -0,0 +1,257 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::options::CONTRACT_SIZE;
+use crate::{Position, parse_number, parse_ticker};
+
+/// What kind of contract a position holds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Instrument {
+    #[default]
+    Equity,
+    Option,
+    Future,
+    Crypto,
+}
+
+impl Instrument {
+    pub(crate) fn is_equity(&self) -> bool {
+        *self == Instrument::Equity
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Instrument::Equity => "equity",
+            Instrument::Option => "option",
+            Instrument::Future => "future",
+            Instrument::Crypto => "crypto",
+        }
+    }
+
+    pub(crate) fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "equity" | "stock" | "etf" | "stk" => Some(Instrument::Equity),
+            "option" | "opt" => Some(Instrument::Option),
+            "future" | "futures" | "fut" => Some(Instrument::Future),
+            "crypto" | "cryptocurrency" => Some(Instrument::Crypto),
+            _ => None,
+        }
+    }
+
+    /// Decimal places quantities are shown with.
+    fn quantity_decimals(self) -> usize {
+        match self {
+            Instrument::Equity => 2,
+            Instrument::Option | Instrument::Future => 0,
+            Instrument::Crypto => 8,
+        }
+    }
+}
+
+/// Contract terms for a symbol: its type, the value of one point per
+/// contract, and how many decimals its quantities need.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct InstrumentSpec {
+    pub(crate) symbol: String,
+    pub(crate) instrument: Instrument,
+    pub(crate) multiplier: f64,
+    pub(crate) decimals: Option<usize>,
+    /// Whether the plain symbol matches. Built-in futures need `/ES` or a
+    /// contract month, so stocks such as ES (Eversource) keep their ticker.
+    bare: bool,
+}
+
+/// Common futures and crypto pairs, available without a registry file.
+const BUILTIN: &[(&str, Instrument, f64)] = &[
+    ("ES", Instrument::Future, 50.0),
+    ("MES", Instrument::Future, 5.0),
+    ("NQ", Instrument::Future, 20.0),
+    ("MNQ", Instrument::Future, 2.0),
+    ("YM", Instrument::Future, 5.0),
+    ("RTY", Instrument::Future, 50.0),
+    ("CL", Instrument::Future, 1000.0),
+    ("GC", Instrument::Future, 100.0),
+    ("BTC-USD", Instrument::Crypto, 1.0),
+    ("ETH-USD", Instrument::Crypto, 1.0),
+];
+
+/// Month codes used in futures contract symbols, e.g. the `H` in `ESH6`.
+const MONTH_CODES: &str = "FGHJKMNQUVXZ";
+
+/// Symbols mapped to contract specs: the built-in list, overridden and
+/// extended by the registry file.
+#[derive(Clone, Debug)]
+pub(crate) struct InstrumentRegistry {
+    specs: Vec<InstrumentSpec>,
+}
+
+impl InstrumentRegistry {
+    pub(crate) fn builtin() -> Self {
+        Self {
+            specs: BUILTIN
+                .iter()
+                .map(|&(symbol, instrument, multiplier)| InstrumentSpec {
+                    symbol: symbol.to_string(),
+                    instrument,
+                    multiplier,
+                    decimals: None,
+                    bare: instrument != Instrument::Future,
+                })
+                .collect(),
+        }
+    }
+
+    /// Reads `symbol,type,multiplier[,decimals]` rows on top of the built-in
+    /// specs. A missing file leaves just the built-in ones.
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        let mut registry = Self::builtin();
+        if !Path::new(path).exists() {
+            return Ok(registry);
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+        registry.extend(&data).map_err(|e| format!("{path}: {e}"))?;
+        Ok(registry)
+    }
+
+    fn extend(&mut self, data: &str) -> Result<(), String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes());
+        for (i, record) in reader.records().enumerate() {
+            let line = i + 2;
+            let record = record.map_err(|e| format!("line {line}: {e}"))?;
+            let field = |n: usize| record.get(n).unwrap_or("");
+            let symbol = parse_ticker(field(0)).map_err(|e| format!("line {line}: {e}"))?;
+            let instrument = Instrument::parse(field(1))
+                .ok_or_else(|| format!("line {line}: unknown type \"{}\"", field(1)))?;
+            let multiplier = match field(2) {
+                "" => 1.0,
+                raw => parse_number(raw)
+                    .filter(|m| *m > 0.0)
+                    .ok_or_else(|| format!("line {line}: invalid multiplier"))?,
+            };
+            let decimals = match field(3) {
+                "" => None,
+                raw => Some(
+                    raw.parse::<usize>()
+                        .map_err(|_| format!("line {line}: invalid decimals"))?,
+                ),
+            };
+            self.specs.retain(|s| s.symbol != symbol);
+            self.specs.push(InstrumentSpec {
+                symbol,
+                instrument,
+                multiplier,
+                decimals,
+                bare: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// The spec for a symbol. Futures also match by root, so `ESH6`,
+    /// `ESZ2025` and `/ES` all find `ES`; `BTC/USD` finds `BTC-USD`.
+    pub(crate) fn lookup(&self, symbol: &str) -> Option<&InstrumentSpec> {
+        let symbol = symbol.trim();
+        let (slashed, symbol) = match symbol.strip_prefix('/') {
+            Some(rest) => (true, rest.replace('/', "-")),
+            None => (false, symbol.replace('/', "-")),
+        };
+        if let Some(spec) = self
+            .specs
+            .iter()
+            .find(|s| s.symbol == symbol && (s.bare || slashed))
+        {
+            return Some(spec);
+        }
+        let root = futures_root(&symbol)?;
+        self.specs
+            .iter()
+            .find(|s| s.instrument == Instrument::Future && s.symbol == root)
+    }
+
+    /// Sets the position's instrument and multiplier from its symbol.
+    /// Options keep their contract size; unknown symbols are left as they are.
+    pub(crate) fn classify(&self, pos: &mut Position) {
+        if pos.option.is_some() {
+            pos.instrument = Instrument::Option;
+            if pos.multiplier == 1.0 {
+                pos.multiplier = CONTRACT_SIZE;
+            }
+            return;
+        }
+        if let Some(spec) = self.lookup(&pos.ticker) {
+            pos.instrument = spec.instrument;
+            pos.multiplier = spec.multiplier;
+        }
+    }
+
+    /// Decimal places for the position's quantity.
+    pub(crate) fn quantity_decimals(&self, pos: &Position) -> usize {
+        self.lookup(&pos.ticker)
+            .and_then(|s| s.decimals)
+            .unwrap_or_else(|| pos.instrument.quantity_decimals())
+    }
+}
+
+/// `ESH6` or `ESH26` → `ES`: a month code and a one- to four-digit year.
+fn futures_root(symbol: &str) -> Option<&str> {
+    let digits = symbol.len() - symbol.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    if !(1..=4).contains(&digits) {
+        return None;
+    }
+    let rest = &symbol[..symbol.len() - digits];
+    let month = rest.chars().last()?;
+    if !MONTH_CODES.contains(month) || rest.len() < 2 {
+        return None;
+    }
+    Some(&rest[..rest.len() - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn futures_match_by_root_and_file_overrides_builtins() {
+        let mut registry = InstrumentRegistry::builtin();
+        registry
+            .extend("symbol,type,multiplier,decimals\nES,future,50\nSOL-USD,crypto,,4\n")
+            .unwrap();
+        for symbol in ["ES", "ESH6", "ESZ2025", "/ESM26"] {
+            assert_eq!(
+                registry.lookup(symbol).unwrap().multiplier,
+                50.0,
+                "{symbol}"
+            );
+        }
+        assert!(registry.lookup("ESSA").is_none());
+        // Built-in futures leave the bare symbol to the stock.
+        assert!(registry.lookup("NQ").is_none());
+        assert_eq!(registry.lookup("/NQ").unwrap().multiplier, 20.0);
+        assert_eq!(
+            registry.lookup("BTC/USD").unwrap().instrument,
+            Instrument::Crypto
+        );
+        assert!(registry.lookup("AAPL").is_none());
+
+        let mut pos = Position {
+            ticker: "MNQH6".into(),
+            ..Position::default()
+        };
+        registry.classify(&mut pos);
+        assert_eq!((pos.instrument, pos.multiplier), (Instrument::Future, 2.0));
+        assert_eq!(registry.quantity_decimals(&pos), 0);
+
+        let sol = Position {
+            ticker: "SOL-USD".into(),
+            ..Position::default()
+        };
+        assert_eq!(registry.quantity_decimals(&sol), 4);
+        assert!(registry.extend("symbol,type\nXYZ,bond\n").is_err());
+    }
+}
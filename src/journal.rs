@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+
+use crate::input::{InputKind, TextArea, TextInput};
+use crate::{Position, PositionSummary, normalize_tag, summarize_positions};
+
+/// Editor for the journal side of a position: its tags and free-form notes.
+#[derive(Clone, Debug)]
+pub(crate) struct JournalForm {
+    /// Comma-separated tags.
+    pub(crate) tags: TextInput,
+    pub(crate) notes: TextArea,
+    pub(crate) notes_focused: bool,
+}
+
+impl JournalForm {
+    pub(crate) fn new(pos: &Position) -> Self {
+        let mut tags = TextInput::new(InputKind::Text);
+        tags.set_value(pos.tags.join(", "));
+        Self {
+            tags,
+            notes: TextArea::new(&pos.notes),
+            notes_focused: false,
+        }
+    }
+
+    /// Writes the edited tags and notes back to the position.
+    pub(crate) fn apply(&self, pos: &mut Position) {
+        pos.tags = parse_tags(self.tags.value());
+        pos.notes = self.notes.text();
+    }
+}
+
+/// Performance of the positions carrying one tag.
+pub(crate) struct TagStats {
+    pub(crate) tag: String,
+    pub(crate) count: usize,
+    pub(crate) wins: usize,
+    pub(crate) summary: PositionSummary,
+}
+
+/// Label used for positions without tags in the breakdown.
+pub(crate) const UNTAGGED: &str = "(untagged)";
+
+/// Groups positions by tag, best total PnL first. A position with several
+/// tags counts toward each of them; untagged positions are grouped last.
+pub(crate) fn tag_breakdown(positions: &[&Position]) -> Vec<TagStats> {
+    let mut by_tag: BTreeMap<&str, Vec<&Position>> = BTreeMap::new();
+    let mut untagged = Vec::new();
+    for &pos in positions {
+        if pos.tags.is_empty() {
+            untagged.push(pos);
+        }
+        for tag in &pos.tags {
+            by_tag.entry(tag.as_str()).or_default().push(pos);
+        }
+    }
+    let stats = |tag: &str, group: &[&Position]| TagStats {
+        tag: tag.to_string(),
+        count: group.len(),
+        wins: group.iter().filter(|p| p.roi_value() > 0.0).count(),
+        summary: summarize_positions(group),
+    };
+    let mut rows: Vec<TagStats> = by_tag
+        .iter()
+        .map(|(tag, group)| stats(tag, group))
+        .collect();
+    rows.sort_by(|a, b| b.summary.total_pnl.total_cmp(&a.summary.total_pnl));
+    if !untagged.is_empty() {
+        rows.push(stats(UNTAGGED, &untagged));
+    }
+    rows
+}
+
+/// Tags as typed in a comma-separated list, normalized.
+pub(crate) fn parse_tags(raw: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in raw.split([',', ';']).map(normalize_tag) {
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(ticker: &str, sale: f64, tags: &[&str]) -> Position {
+        Position {
+            ticker: ticker.into(),
+            cost_per_share: 10.0,
+            quantity: 1.0,
+            sale_price: sale,
+            tags: parse_tags(&tags.join(",")),
+            ..Position::default()
+        }
+    }
+
+    #[test]
+    fn breakdown_counts_each_tag_and_untagged() {
+        let positions = [
+            pos("AAPL", 15.0, &["breakout"]),
+            pos("MSFT", 8.0, &["Breakout", "mistake"]),
+            pos("NVDA", 12.0, &[]),
+        ];
+        let refs: Vec<&Position> = positions.iter().collect();
+        let rows = tag_breakdown(&refs);
+        let tags: Vec<&str> = rows.iter().map(|r| r.tag.as_str()).collect();
+        assert_eq!(tags, ["breakout", "mistake", UNTAGGED]);
+        assert_eq!(rows[0].count, 2);
+        assert_eq!(rows[0].wins, 1);
+        assert!((rows[0].summary.total_pnl - 3.0).abs() < 1e-9);
+        assert!((rows[1].summary.total_pnl + 2.0).abs() < 1e-9);
+    }
+}
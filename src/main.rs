@@ -1,18 +1,19 @@
 use std::{
+    collections::BTreeSet,
     error::Error,
     fs,
     io::{self, stdout},
     path::Path,
-    time::Duration,
+    sync::mpsc::{Receiver, TryRecvError},
+    time::{Duration, Instant},
 };
 
 use chrono::NaiveDate;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyModifiers},
+    event::{self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
-use csv::Trim;
 use ratatui::{
     Frame, Terminal,
     backend::CrosstermBackend,
@@ -21,19 +22,83 @@ use ratatui::{
     symbols,
     text::{Line, Span},
     widgets::{
-        Axis, Block, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, Paragraph, Row,
-        Table, TableState,
+        Axis, BarChart, Block, Borders, Cell, Chart, Dataset, GraphType, List, ListItem, ListState,
+        Paragraph, Row, Table, TableState,
     },
 };
 use serde::{Deserialize, Serialize};
 
+mod benchmark;
+mod brokers;
+mod bulk;
+mod cli;
+mod config;
+mod dividends;
+mod export;
+mod flex;
+mod fx;
+mod import;
+mod input;
+mod instruments;
+mod journal;
+mod mapping;
+mod ofx;
+mod options;
+mod picker;
+mod prices;
+mod quotes;
+mod risk;
+mod sheet;
+mod sizing;
+mod splits;
+mod tax;
+mod trash;
+
+use benchmark::{Comparison, compare, equity_curve};
+use bulk::BulkEdit;
+use config::{Config, load_config, save_config};
+use dividends::{Dividend, attribute, merge_dividends, unattributed};
+use flex::parse_flex_file;
+use fx::{FxRates, format_money};
+use import::{
+    DuplicatePolicy, ImportReport, MatchStatus, NativeCells, parse_import_records,
+    parse_position_records, read_csv_records,
+};
+use input::{InputKind, TextInput};
+use instruments::{Instrument, InstrumentRegistry};
+use journal::{JournalForm, tag_breakdown};
+use mapping::{
+    DATE_FORMATS, FIELD_LABELS, MappingWizard, ROW_DATE_FORMAT, ROW_NAME, ROW_NUMBER_FORMAT,
+};
+use options::{CONTRACT_SIZE, OptionContract, parse_symbol};
+use picker::{PathEntry, complete, expand_path, matching_entries};
+use prices::{
+    Excursion, PriceHistory, PriceStore, excursion, exit_efficiency, mark_to_market, roi_path,
+};
+use quotes::{QuoteCache, Refreshed, spawn_refresh};
+use risk::{R_BUCKETS, RStats, RiskMetrics, daily_returns, r_stats, risk_metrics};
+use sheet::{SheetRecords, WORKBOOK_EXTENSIONS, read_sheet, sheet_names};
+use sizing::{Sizing, SizingForm};
+use splits::{EnteredValues, Split, apply_splits, merge_splits, parse_split_records};
+use tax::{TaxableGains, taxable_gains};
+use trash::{TrashEntry, purge_expired};
+
 const DATE_FMT: &str = "%Y-%m-%d";
 const DATA_FILE: &str = "positions.json";
 
 fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = cli::run(&args) {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
     enable_raw_mode()?;
     let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableBracketedPaste)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -41,7 +106,11 @@ fn main() -> Result<(), Box<dyn Error>> {
     let res = run_app(&mut terminal, app);
 
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    )?;
     terminal.show_cursor()?;
 
     if let Err(err) = res {
@@ -57,6 +126,7 @@ fn run_app(
     let tick_rate = Duration::from_millis(200);
 
     loop {
+        app.poll_refresh();
         terminal.draw(|f| ui(f, &app))?;
 
         if !event::poll(tick_rate)? {
@@ -73,11 +143,11 @@ fn run_app(
                             app.filter_text.pop();
                             app.ensure_selection_visible();
                         }
-                        KeyCode::Char(c) => {
-                            if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT {
-                                app.filter_text.push(c);
-                                app.ensure_selection_visible();
-                            }
+                        KeyCode::Char(c)
+                            if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+                        {
+                            app.filter_text.push(c);
+                            app.ensure_selection_visible();
                         }
                         _ => {}
                     }
@@ -92,14 +162,11 @@ fn run_app(
                             app.form = AddForm::new();
                             app.editing = None;
                         }
-                        KeyCode::Char('i') => {
-                            app.mode = Mode::Import;
-                            app.import_form = ImportForm::new();
-                        }
-                        KeyCode::Char('d') | KeyCode::Enter => {
-                            if !app.filtered_positions().is_empty() {
-                                app.mode = Mode::Detail;
-                            }
+                        KeyCode::Char('i') => app.open_import_form(),
+                        KeyCode::Char('d') | KeyCode::Enter
+                            if !app.filtered_positions().is_empty() =>
+                        {
+                            app.mode = Mode::Detail;
                         }
                         KeyCode::Char('e') => {
                             if let Some(pos) = app.selected_position().cloned() {
@@ -108,8 +175,37 @@ fn run_app(
                                 app.form = AddForm::from_position(&pos);
                             }
                         }
-                        KeyCode::Char('x') | KeyCode::Delete => {
-                            app.delete_selected();
+                        KeyCode::Char('x') | KeyCode::Delete => app.request_delete(),
+                        KeyCode::Char('t') => app.open_trash(),
+                        KeyCode::Char(' ') => app.toggle_mark(),
+                        KeyCode::Char('V') => app.mark_range(),
+                        KeyCode::Char('A') => app.mark_all_filtered(),
+                        KeyCode::Char('m') => app.summary_marked_only = !app.summary_marked_only,
+                        KeyCode::Char('g') => app.mode = Mode::Tags,
+                        KeyCode::Char('r') => app.mode = Mode::Risk,
+                        KeyCode::Char('s') => {
+                            app.sizing = SizingForm::new(app.account_equity());
+                            app.mode = Mode::Sizing;
+                        }
+                        KeyCode::Char('c') => {
+                            app.splits_message = None;
+                            app.mode = Mode::Splits;
+                        }
+                        KeyCode::Char('$') => app.mode = Mode::Dividends,
+                        KeyCode::Char('R') => app.toggle_total_return(),
+                        KeyCode::Char('P') => {
+                            app.refresh_prices();
+                            app.start_refresh(true);
+                        }
+                        KeyCode::Char(']') => app.switch_account(true),
+                        KeyCode::Char('[') => app.switch_account(false),
+                        KeyCode::Char('B') if !app.marked.is_empty() => {
+                            app.bulk = BulkEdit::new();
+                            app.mode = Mode::BulkEdit;
+                        }
+                        KeyCode::Esc => {
+                            app.marked.clear();
+                            app.mark_anchor = None;
                         }
                         KeyCode::Char('h') => app.mode = Mode::Help,
                         KeyCode::Char('f') | KeyCode::Char('/') => {
@@ -125,8 +221,7 @@ fn run_app(
                         KeyCode::Down => app.select_next(),
                         KeyCode::Up => app.select_prev(),
                         KeyCode::Char('i') => {
-                            app.mode = Mode::Import;
-                            app.import_form = ImportForm::new();
+                            app.open_import_form();
                         }
                         KeyCode::Char('a') => {
                             app.mode = Mode::AddForm;
@@ -140,15 +235,162 @@ fn run_app(
                                 app.form = AddForm::from_position(&pos);
                             }
                         }
-                        KeyCode::Char('x') | KeyCode::Delete => {
-                            app.delete_selected();
-                            app.mode = Mode::Portfolio;
+                        KeyCode::Char('x') | KeyCode::Delete => app.request_delete(),
+                        KeyCode::Char('n') => {
+                            if let Some(pos) = app.selected_position() {
+                                app.journal = Some(JournalForm::new(pos));
+                                app.mode = Mode::Journal;
+                            }
                         }
                         KeyCode::Char('f') | KeyCode::Char('/') => {
                             app.filter_editing = true;
                         }
                         _ => {}
                     },
+                    Mode::Journal => {
+                        let Some(journal) = app.journal.as_mut() else {
+                            app.mode = Mode::Detail;
+                            continue;
+                        };
+                        match key.code {
+                            KeyCode::Esc => {
+                                app.journal = None;
+                                app.mode = Mode::Detail;
+                            }
+                            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                                if let Some(pos) = app.positions.get_mut(app.selected) {
+                                    journal.apply(pos);
+                                }
+                                app.journal = None;
+                                app.save();
+                                app.mode = Mode::Detail;
+                            }
+                            KeyCode::Tab | KeyCode::BackTab => {
+                                journal.notes_focused = !journal.notes_focused;
+                            }
+                            KeyCode::Enter if !journal.notes_focused => {
+                                journal.notes_focused = true;
+                            }
+                            _ if journal.notes_focused => {
+                                journal.notes.handle_key(key);
+                            }
+                            _ => {
+                                journal.tags.handle_key(key);
+                            }
+                        }
+                    }
+                    Mode::Splits => match key.code {
+                        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('c') => {
+                            app.mode = Mode::Portfolio
+                        }
+                        KeyCode::Char('q') => break,
+                        KeyCode::Down if app.splits_selected + 1 < app.splits.len() => {
+                            app.splits_selected += 1;
+                        }
+                        KeyCode::Up => app.splits_selected = app.splits_selected.saturating_sub(1),
+                        KeyCode::Char('x') | KeyCode::Delete => app.delete_split(),
+                        _ => {}
+                    },
+                    Mode::Dividends => match key.code {
+                        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('$') => {
+                            app.mode = Mode::Portfolio
+                        }
+                        KeyCode::Char('q') => break,
+                        KeyCode::Down if app.dividends_selected + 1 < app.dividends.len() => {
+                            app.dividends_selected += 1;
+                        }
+                        KeyCode::Up => {
+                            app.dividends_selected = app.dividends_selected.saturating_sub(1)
+                        }
+                        KeyCode::Char('x') | KeyCode::Delete => app.delete_dividend(),
+                        KeyCode::Char('e') | KeyCode::Enter => app.edit_ex_date(),
+                        _ => {}
+                    },
+                    Mode::ExDate => match key.code {
+                        KeyCode::Esc => app.mode = Mode::Dividends,
+                        KeyCode::Enter => match app.set_ex_date() {
+                            Ok(()) => app.mode = Mode::Dividends,
+                            Err(err) => app.ex_date_error = Some(err),
+                        },
+                        _ => {
+                            app.ex_date_input.handle_key(key);
+                            app.ex_date_error = None;
+                        }
+                    },
+                    Mode::Tags => match key.code {
+                        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('g') => {
+                            app.mode = Mode::Portfolio
+                        }
+                        KeyCode::Char('q') => break,
+                        _ => {}
+                    },
+                    Mode::Sizing => match key.code {
+                        KeyCode::Esc => app.mode = Mode::Portfolio,
+                        KeyCode::Tab | KeyCode::Down => app.sizing.next_field(),
+                        KeyCode::BackTab | KeyCode::Up => app.sizing.prev_field(),
+                        KeyCode::Enter => match app.size_position() {
+                            Ok(sizing) => {
+                                app.form = AddForm::from_sizing(&app.sizing, sizing.quantity);
+                                app.editing = None;
+                                app.mode = Mode::AddForm;
+                            }
+                            Err(err) => app.sizing.error = Some(err),
+                        },
+                        _ => {
+                            app.sizing.active_input().handle_key(key);
+                            app.sizing.error = None;
+                        }
+                    },
+                    Mode::Risk => match key.code {
+                        KeyCode::Esc | KeyCode::Char('b') | KeyCode::Char('r') => {
+                            app.mode = Mode::Portfolio
+                        }
+                        KeyCode::Char('q') => break,
+                        _ => {}
+                    },
+                    Mode::ConfirmDelete => match key.code {
+                        KeyCode::Char('y') | KeyCode::Enter => {
+                            app.delete_selected();
+                            app.mode = Mode::Portfolio;
+                        }
+                        KeyCode::Char('n') | KeyCode::Esc => {
+                            app.mode = app
+                                .pending_delete
+                                .take()
+                                .map_or(Mode::Portfolio, |(_, m)| m);
+                        }
+                        _ => {}
+                    },
+                    Mode::BulkEdit => match key.code {
+                        KeyCode::Esc => app.mode = Mode::Portfolio,
+                        KeyCode::Tab => app.bulk.cycle_action(),
+                        KeyCode::Enter => {
+                            match app
+                                .bulk
+                                .apply(&mut app.positions, &app.marked, &app.instruments)
+                            {
+                                Ok(_) => {
+                                    app.save();
+                                    app.mode = Mode::Portfolio;
+                                }
+                                Err(err) => app.bulk.error = Some(err),
+                            }
+                        }
+                        _ => {
+                            app.bulk.input.handle_key(key);
+                        }
+                    },
+                    Mode::Trash => match key.code {
+                        KeyCode::Esc | KeyCode::Char('b') => app.mode = Mode::Portfolio,
+                        KeyCode::Char('q') => break,
+                        KeyCode::Down if app.trash_selected + 1 < app.trash.len() => {
+                            app.trash_selected += 1;
+                        }
+                        KeyCode::Up => app.trash_selected = app.trash_selected.saturating_sub(1),
+                        KeyCode::Char('r') | KeyCode::Enter => app.restore_from_trash(),
+                        KeyCode::Char('x') | KeyCode::Delete => app.purge_from_trash(),
+                        _ => {}
+                    },
                     Mode::Help => match key.code {
                         KeyCode::Esc | KeyCode::Char('b') | KeyCode::Enter => {
                             app.mode = Mode::Portfolio
@@ -159,19 +401,31 @@ fn run_app(
                     Mode::Import => match key.code {
                         KeyCode::Esc => {
                             app.mode = Mode::Portfolio;
-                            app.import_form = ImportForm::new();
+                            app.import_form = ImportForm::new(&app.config.recent_imports);
+                        }
+                        KeyCode::BackTab => {
+                            app.import_form.account_focused = !app.import_form.account_focused;
+                        }
+                        KeyCode::Tab if app.import_form.account_focused => {
+                            let accounts = app.accounts();
+                            app.import_form.cycle_account(&accounts);
+                        }
+                        _ if app.import_form.account_focused && key.code != KeyCode::Enter => {
+                            app.import_form.account.handle_key(key);
                         }
                         KeyCode::Enter => {
-                            let path = app.import_form.path.trim().to_string();
+                            app.import_account = parse_account(app.import_form.account.value());
+                            let Some(path) = app.import_form.choose() else {
+                                continue;
+                            };
                             if path.is_empty() {
                                 app.import_form.error = Some("Path cannot be empty".into());
                             } else {
-                                match app.import_csv(&path) {
-                                    Ok(count) => {
-                                        app.import_form.message =
-                                            Some(format!("Imported {count} positions"));
+                                match app.preview_import(&path) {
+                                    Ok(mode) => {
                                         app.import_form.error = None;
-                                        app.mode = Mode::Portfolio;
+                                        app.import_form.message = None;
+                                        app.mode = mode;
                                     }
                                     Err(err) => {
                                         app.import_form.error = Some(err);
@@ -180,12 +434,118 @@ fn run_app(
                                 }
                             }
                         }
-                        KeyCode::Backspace => app.import_form.backspace(),
-                        KeyCode::Char(c) => {
-                            if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT {
-                                app.import_form.push_char(c);
+                        KeyCode::Tab => app.import_form.complete(),
+                        KeyCode::Down => app.import_form.select_next(),
+                        KeyCode::Up => app.import_form.select_prev(),
+                        _ => app.import_form.handle_key(key),
+                    },
+                    Mode::ImportSheet => match key.code {
+                        KeyCode::Esc => {
+                            app.sheet_picker = None;
+                            app.mode = Mode::Import;
+                        }
+                        KeyCode::Down | KeyCode::Up => {
+                            if let Some(picker) = app.sheet_picker.as_mut() {
+                                let n = picker.sheets.len();
+                                picker.cursor = if key.code == KeyCode::Down {
+                                    (picker.cursor + 1) % n
+                                } else {
+                                    (picker.cursor + n - 1) % n
+                                };
+                            }
+                        }
+                        KeyCode::Enter => {
+                            if let Some(picker) = &app.sheet_picker {
+                                let path = picker.path.clone();
+                                let sheet = picker.sheets[picker.cursor].clone();
+                                match app.preview_sheet(&path, &sheet) {
+                                    Ok(mode) => app.mode = mode,
+                                    Err(err) => {
+                                        app.sheet_picker = None;
+                                        app.import_form.error = Some(err);
+                                        app.mode = Mode::Import;
+                                    }
+                                }
+                            }
+                        }
+                        KeyCode::Char('q') => break,
+                        _ => {}
+                    },
+                    Mode::ImportMapping => match key.code {
+                        KeyCode::Esc => {
+                            app.mapping_wizard = None;
+                            app.mode = Mode::Import;
+                        }
+                        KeyCode::Enter => match app.apply_mapping_wizard() {
+                            Ok(mode) => app.mode = mode,
+                            Err(err) => {
+                                if let Some(wizard) = app.mapping_wizard.as_mut() {
+                                    wizard.error = Some(err);
+                                }
+                            }
+                        },
+                        _ => {
+                            if let Some(wizard) = app.mapping_wizard.as_mut() {
+                                match key.code {
+                                    KeyCode::Down | KeyCode::Tab => wizard.next_row(),
+                                    KeyCode::Up | KeyCode::BackTab => wizard.prev_row(),
+                                    KeyCode::Right => wizard.cycle(true),
+                                    KeyCode::Left => wizard.cycle(false),
+                                    KeyCode::PageDown => wizard.move_header(true),
+                                    KeyCode::PageUp => wizard.move_header(false),
+                                    KeyCode::Backspace => wizard.backspace(),
+                                    KeyCode::Char(c)
+                                        if key.modifiers.is_empty()
+                                            || key.modifiers == KeyModifiers::SHIFT =>
+                                    {
+                                        wizard.push_char(c)
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        }
+                    },
+                    Mode::ImportPreview => match key.code {
+                        KeyCode::Esc => {
+                            app.import_preview = None;
+                            app.mode = Mode::Import;
+                        }
+                        KeyCode::Enter => {
+                            app.import_form.message = Some(app.commit_import());
+                            app.import_form.error = None;
+                            app.mode = Mode::Portfolio;
+                        }
+                        KeyCode::Char('p') => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.policy = preview.policy.next();
+                            }
+                        }
+                        KeyCode::Down => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.select_next();
+                            }
+                        }
+                        KeyCode::Up => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.select_prev();
+                            }
+                        }
+                        KeyCode::Char(' ') => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.toggle_current();
+                            }
+                        }
+                        KeyCode::Char('a') => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.toggle_all();
+                            }
+                        }
+                        KeyCode::Char('d') => {
+                            if let Some(preview) = app.import_preview.as_mut() {
+                                preview.include_dividends = !preview.include_dividends;
                             }
                         }
+                        KeyCode::Char('q') => break,
                         _ => {}
                     },
                     Mode::AddForm => match key.code {
@@ -197,16 +557,18 @@ fn run_app(
                         KeyCode::Enter => {
                             if app.form.on_enter() {
                                 match app.form.try_build_position() {
-                                    Ok(pos) => {
+                                    Ok(mut pos) => {
+                                        app.instruments.classify(&mut pos);
                                         if let Some(idx) = app.editing {
                                             app.positions[idx] = pos;
                                             app.selected = idx;
                                         } else {
+                                            pos.account = app.account.clone();
                                             app.positions.push(pos);
                                             app.selected = app.positions.len().saturating_sub(1);
                                         }
                                         app.ensure_selection_visible();
-                                        save_positions(&app.positions);
+                                        app.save();
                                         app.mode = Mode::Portfolio;
                                         app.editing = None;
                                         app.form.error = None;
@@ -217,20 +579,34 @@ fn run_app(
                                 app.form.next_field();
                             }
                         }
-                        KeyCode::Tab => app.form.next_field(),
-                        KeyCode::BackTab => app.form.prev_field(),
-                        KeyCode::Backspace => app.form.backspace(),
-                        KeyCode::Left => app.form.backspace(),
-                        KeyCode::Right => app.form.next_field(),
-                        KeyCode::Char(c) => {
-                            if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT {
-                                app.form.push_char(c);
-                            }
+                        KeyCode::Tab | KeyCode::Down => app.form.next_field(),
+                        KeyCode::BackTab | KeyCode::Up => app.form.prev_field(),
+                        _ => {
+                            app.form.active_input().handle_key(key);
                         }
-                        _ => {}
                     },
                 }
             }
+            Event::Paste(text) => match app.mode {
+                Mode::AddForm => app.form.active_input().insert_str(&text),
+                Mode::Import if app.import_form.account_focused => {
+                    app.import_form.account.insert_str(&text)
+                }
+                Mode::Import => app.import_form.paste(&text),
+                Mode::BulkEdit => app.bulk.input.insert_str(&text),
+                Mode::ExDate => app.ex_date_input.insert_str(&text),
+                Mode::Sizing => app.sizing.active_input().insert_str(&text),
+                Mode::Journal => {
+                    if let Some(journal) = app.journal.as_mut() {
+                        if journal.notes_focused {
+                            journal.notes.insert_str(&text);
+                        } else {
+                            journal.tags.insert_str(&text);
+                        }
+                    }
+                }
+                _ => {}
+            },
             Event::Resize(_, _) => {} // redraw happens next loop
             _ => {}
         }
@@ -239,7 +615,7 @@ fn run_app(
     Ok(())
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 struct Position {
     ticker: String,
     cost_per_share: f64,
@@ -247,25 +623,139 @@ struct Position {
     sale_price: f64,
     purchase_date: NaiveDate,
     sale_date: NaiveDate,
+    /// Broker-assigned lot identifier, when the export provides one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    lot_id: Option<String>,
+    /// Commissions and fees not already included in the prices.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    fees: f64,
+    /// ISO currency code the prices are quoted in, when not the default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    currency: Option<String>,
+    /// Units of the underlying per contract (1 for shares).
+    #[serde(default = "default_multiplier", skip_serializing_if = "is_one")]
+    multiplier: f64,
+    /// Setup labels such as "breakout" or "earnings", lowercase.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Free-form trade journal text; may span several lines.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    notes: String,
+    /// Brokerage account or portfolio the position belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    account: Option<String>,
+    /// The values as entered, while split adjustments have replaced them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entered: Option<EnteredValues>,
+    /// Imported values already reflect splits up to this date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    adjusted_through: Option<NaiveDate>,
+    /// Strike, expiry and type when the position is an option on `ticker`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    option: Option<OptionContract>,
+    #[serde(default, skip_serializing_if = "Instrument::is_equity")]
+    instrument: Instrument,
+    /// Not sold yet: the sale price and date hold the latest mark.
+    #[serde(default, skip_serializing_if = "is_false")]
+    open: bool,
+    /// Planned exit if the trade goes wrong; sets the trade's initial risk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    stop_price: Option<f64>,
+    /// Planned exit if the trade works.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_price: Option<f64>,
+    /// Dividend income counted in returns; set only in total-return mode.
+    #[serde(skip)]
+    income: f64,
+}
+
+impl Default for Position {
+    fn default() -> Self {
+        Self {
+            ticker: String::new(),
+            cost_per_share: 0.0,
+            quantity: 0.0,
+            sale_price: 0.0,
+            purchase_date: NaiveDate::default(),
+            sale_date: NaiveDate::default(),
+            lot_id: None,
+            fees: 0.0,
+            currency: None,
+            multiplier: default_multiplier(),
+            tags: Vec::new(),
+            notes: String::new(),
+            account: None,
+            entered: None,
+            adjusted_through: None,
+            option: None,
+            instrument: Instrument::Equity,
+            open: false,
+            stop_price: None,
+            target_price: None,
+            income: 0.0,
+        }
+    }
+}
+
+/// Tags are compared case-insensitively and never contain spaces.
+fn normalize_tag(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+fn is_zero(v: &f64) -> bool {
+    *v == 0.0
+}
+
+fn is_one(v: &f64) -> bool {
+    *v == 1.0
+}
+
+fn is_false(v: &bool) -> bool {
+    !*v
 }
 
 impl Position {
     fn invested(&self) -> f64 {
-        self.cost_per_share * self.quantity
+        self.cost_per_share * self.quantity * self.multiplier
     }
 
     fn proceeds(&self) -> f64 {
-        self.sale_price * self.quantity
+        self.sale_price * self.quantity * self.multiplier
     }
 
     fn roi_value(&self) -> f64 {
-        self.proceeds() - self.invested()
+        self.proceeds() - self.invested() - self.fees + self.income
     }
 
     fn roi_pct(&self) -> f64 {
         self.roi_value() / self.invested()
     }
 
+    /// Loss per share if the stop is hit; `None` without a stop below cost.
+    fn initial_risk(&self) -> Option<f64> {
+        self.stop_price
+            .map(|stop| self.cost_per_share - stop)
+            .filter(|risk| *risk > 0.0)
+    }
+
+    /// PnL in units of the initial risk.
+    fn r_multiple(&self) -> Option<f64> {
+        let risk = self.initial_risk()? * self.quantity * self.multiplier;
+        Some(self.roi_value() / risk)
+    }
+
+    /// Planned gain at the target per unit of initial risk.
+    fn reward_risk(&self) -> Option<f64> {
+        Some((self.target_price? - self.cost_per_share) / self.initial_risk()?)
+    }
+
     fn days_held(&self) -> i64 {
         let days = (self.sale_date - self.purchase_date).num_days();
         days.max(1)
@@ -276,30 +766,125 @@ impl Position {
     }
 
     fn annualized_roi(&self) -> f64 {
-        let multiple = self.proceeds() / self.invested();
+        let multiple = (self.invested() + self.roi_value()) / self.invested();
         if multiple <= 0.0 {
             return -1.0;
         }
         let years = self.days_held() as f64 / 365.0;
         multiple.powf(1.0 / years) - 1.0
     }
+
+    /// Identifies the lot itself: the broker lot id when known, otherwise
+    /// symbol, dates and quantity. Two rows with the same key but different
+    /// fingerprints describe the same lot with changed values.
+    fn identity_key(&self) -> String {
+        match &self.lot_id {
+            Some(id) => format!("lot:{id}"),
+            None => format!(
+                "{}|{}|{}|{:.6}",
+                self.symbol(),
+                self.purchase_date,
+                self.sale_date,
+                self.quantity
+            ),
+        }
+    }
+
+    /// The ticker, or the OCC symbol for an option.
+    fn symbol(&self) -> String {
+        match &self.option {
+            Some(contract) => contract.occ_symbol(&self.ticker),
+            None => self.ticker.clone(),
+        }
+    }
+
+    /// The ticker, or a short description of an option contract.
+    fn label(&self) -> String {
+        match &self.option {
+            Some(contract) => contract.label(&self.ticker),
+            None => self.ticker.clone(),
+        }
+    }
+
+    /// Sets the underlying and contract parsed from a symbol. Options are
+    /// priced per share and quantities count contracts, so they take the
+    /// standard contract size as multiplier.
+    fn set_symbol(&mut self, ticker: String, option: Option<OptionContract>) {
+        if option.is_some() {
+            self.instrument = Instrument::Option;
+            if self.multiplier == 1.0 {
+                self.multiplier = CONTRACT_SIZE;
+            }
+        } else if self.option.is_some() {
+            self.instrument = Instrument::Equity;
+            if self.multiplier == CONTRACT_SIZE {
+                self.multiplier = 1.0;
+            }
+        }
+        self.ticker = ticker;
+        self.option = option;
+    }
+
+    fn add_tag(&mut self, tag: &str) {
+        let tag = normalize_tag(tag);
+        if !tag.is_empty() && !self.tags.contains(&tag) {
+            self.tags.push(tag);
+        }
+    }
+
+    fn remove_tag(&mut self, tag: &str) {
+        let tag = normalize_tag(tag);
+        self.tags.retain(|t| *t != tag);
+    }
+
+    fn fingerprint(&self) -> String {
+        format!(
+            "{}|{}|{}|{:.6}|{:.4}|{:.4}|{}",
+            self.symbol(),
+            self.purchase_date,
+            self.sale_date,
+            self.quantity,
+            self.cost_per_share,
+            self.sale_price,
+            self.lot_id.as_deref().unwrap_or("")
+        )
+    }
+
+    /// Takes the broker-reported values from a re-imported row, keeping the
+    /// journal (tags, notes, stop and target) and the account. The values as
+    /// entered are dropped: the broker's are already split-adjusted.
+    fn update_from_import(&mut self, imported: Position) {
+        self.cost_per_share = imported.cost_per_share;
+        self.quantity = imported.quantity;
+        self.sale_price = imported.sale_price;
+        self.purchase_date = imported.purchase_date;
+        self.sale_date = imported.sale_date;
+        self.fees = imported.fees;
+        self.lot_id = imported.lot_id;
+        self.adjusted_through = imported.adjusted_through;
+        self.entered = None;
+    }
 }
 
 #[derive(Clone)]
 struct Field {
     label: &'static str,
     placeholder: &'static str,
-    value: String,
+    input: TextInput,
 }
 
 impl Field {
-    fn new(label: &'static str, placeholder: &'static str) -> Self {
+    fn new(label: &'static str, placeholder: &'static str, kind: InputKind) -> Self {
         Self {
             label,
             placeholder,
-            value: String::new(),
+            input: TextInput::new(kind),
         }
     }
+
+    fn value(&self) -> &str {
+        self.input.value()
+    }
 }
 
 #[derive(Clone)]
@@ -307,6 +892,8 @@ struct AddForm {
     fields: Vec<Field>,
     active: usize,
     error: Option<String>,
+    /// Position being edited; fields the form doesn't show are kept from it.
+    source: Option<Position>,
 }
 
 impl Default for AddForm {
@@ -319,29 +906,124 @@ impl AddForm {
     fn new() -> Self {
         Self {
             fields: vec![
-                Field::new("Ticker", "e.g. AAPL"),
-                Field::new("Cost/share", "e.g. 112.40"),
-                Field::new("Quantity", "e.g. 50"),
-                Field::new("Sale price", "e.g. 128.70"),
-                Field::new("Purchase date", "YYYY-MM-DD"),
-                Field::new("Sale date", "YYYY-MM-DD"),
+                Field::new(
+                    "Ticker",
+                    "e.g. AAPL or AAPL  260220C00150000",
+                    InputKind::Text,
+                ),
+                Field::new("Cost/share", "e.g. 112.40", InputKind::Number),
+                Field::new("Quantity", "e.g. 50", InputKind::Number),
+                Field::new("Sale price", "e.g. 128.70", InputKind::Number),
+                Field::new("Purchase date", "YYYY-MM-DD", InputKind::Date),
+                Field::new("Sale date", "YYYY-MM-DD", InputKind::Date),
+                Field::new("Currency", "blank for base, e.g. GBP", InputKind::Text),
+                Field::new("Stop", "optional, e.g. 104.00", InputKind::Number),
+                Field::new("Target", "optional, e.g. 130.00", InputKind::Number),
             ],
             active: 0,
             error: None,
+            source: None,
         }
     }
 
+    /// Fills the form with the position as it was entered, before any
+    /// split adjustment.
     fn from_position(pos: &Position) -> Self {
         let mut form = Self::new();
-        form.fields[0].value = pos.ticker.clone();
-        form.fields[1].value = format!("{:.2}", pos.cost_per_share);
-        form.fields[2].value = format!("{:.4}", pos.quantity);
-        form.fields[3].value = format!("{:.2}", pos.sale_price);
-        form.fields[4].value = pos.purchase_date.format(DATE_FMT).to_string();
-        form.fields[5].value = pos.sale_date.format(DATE_FMT).to_string();
+        form.source = Some(pos.clone());
+        let pos = &match pos.entered {
+            Some(entered) => Position {
+                cost_per_share: entered.cost_per_share,
+                quantity: entered.quantity,
+                sale_price: entered.sale_price,
+                stop_price: entered.stop_price,
+                target_price: entered.target_price,
+                ..pos.clone()
+            },
+            None => pos.clone(),
+        };
+        form.fields[0].input.set_value(pos.symbol());
+        form.fields[1]
+            .input
+            .set_value(format_exact(pos.cost_per_share, 2));
+        form.fields[2]
+            .input
+            .set_value(format_exact(pos.quantity, 4));
+        // An open position's sale fields hold its mark; they stay blank.
+        if !pos.open {
+            form.fields[3]
+                .input
+                .set_value(format_exact(pos.sale_price, 2));
+            form.fields[5]
+                .input
+                .set_value(pos.sale_date.format(DATE_FMT).to_string());
+        }
+        form.fields[4]
+            .input
+            .set_value(pos.purchase_date.format(DATE_FMT).to_string());
+        form.fields[6]
+            .input
+            .set_value(pos.currency.clone().unwrap_or_default());
+        if let Some(stop) = pos.stop_price {
+            form.fields[7].input.set_value(format_exact(stop, 2));
+        }
+        if let Some(target) = pos.target_price {
+            form.fields[8].input.set_value(format_exact(target, 2));
+        }
+        form
+    }
+
+    /// A new position from the sizing calculator, bought today; the sale
+    /// stays blank so it is recorded as open.
+    fn from_sizing(sizing: &SizingForm, quantity: f64) -> Self {
+        let mut form = Self::new();
+        form.fields[0].input.set_value(sizing.value(0).trim());
+        if let Ok(entry) = sizing.entry() {
+            form.fields[1].input.set_value(format_exact(entry, 2));
+        }
+        form.fields[2].input.set_value(format_exact(quantity, 0));
+        form.fields[4].input.set_value(
+            chrono::Local::now()
+                .date_naive()
+                .format(DATE_FMT)
+                .to_string(),
+        );
+        if let Ok(stop) = sizing.stop() {
+            form.fields[7].input.set_value(format_exact(stop, 2));
+        }
         form
     }
 
+    /// Stop and target as typed; blank fields are `None`.
+    fn plan(&self) -> Result<(Option<f64>, Option<f64>), String> {
+        let optional = |idx: usize, name: &str| match self.fields[idx].value().trim() {
+            "" => Ok(None),
+            raw => parse_f64(raw, name).map(Some),
+        };
+        Ok((optional(7, "stop")?, optional(8, "target")?))
+    }
+
+    /// The planned risk and reward:risk as typed so far, for the form.
+    fn plan_summary(&self) -> Option<String> {
+        let cost = parse_number(self.fields[1].value())?;
+        let (stop, target) = self.plan().ok()?;
+        let preview = Position {
+            cost_per_share: cost,
+            stop_price: stop,
+            target_price: target,
+            ..Position::default()
+        };
+        let risk = preview.initial_risk()?;
+        let mut summary = format!("Risk {risk:.2} per share");
+        if let Some(qty) = parse_number(self.fields[2].value()) {
+            summary.push_str(&format!(" ({:.2} in all)", risk * qty));
+        }
+        if let Some(ratio) = preview.reward_risk() {
+            summary.push_str(&format!("  •  reward:risk {ratio:.2}:1"));
+        }
+        Some(summary)
+    }
+
     fn on_enter(&self) -> bool {
         self.active >= self.fields.len().saturating_sub(1)
     }
@@ -358,75 +1040,262 @@ impl AddForm {
         }
     }
 
-    fn backspace(&mut self) {
-        if let Some(ch) = self.fields.get_mut(self.active) {
-            ch.value.pop();
-        }
-    }
-
-    fn push_char(&mut self, c: char) {
-        if let Some(ch) = self.fields.get_mut(self.active) {
-            ch.value.push(c);
-        }
+    fn active_input(&mut self) -> &mut TextInput {
+        &mut self.fields[self.active].input
     }
 
     fn try_build_position(&self) -> Result<Position, String> {
-        let ticker = parse_ticker(&self.fields[0].value)?;
-        let cost = parse_f64(&self.fields[1].value, "cost/share")?;
-        let qty = parse_f64(&self.fields[2].value, "quantity")?;
-        let sale_price = parse_f64(&self.fields[3].value, "sale price")?;
-        let purchase_date = parse_date(&self.fields[4].value, "purchase date")?;
-        let sale_date = parse_date(&self.fields[5].value, "sale date")?;
+        let (ticker, option) = parse_symbol(self.fields[0].value())?;
+        let cost = parse_f64(self.fields[1].value(), "cost/share")?;
+        let qty = parse_f64(self.fields[2].value(), "quantity")?;
+        let purchase_date = parse_date(self.fields[4].value(), "purchase date")?;
+        let unsold =
+            self.fields[3].value().trim().is_empty() && self.fields[5].value().trim().is_empty();
+        let today = chrono::Local::now().date_naive();
+        let (sale_price, sale_date, open) = match option {
+            // An option left without a sale after its expiry expired worthless.
+            Some(contract) if unsold && contract.expiry < today => (0.0, contract.expiry, false),
+            // Otherwise no sale means still held; it is marked to market on save.
+            _ if unsold => (cost, today.max(purchase_date), true),
+            _ => (
+                parse_f64(self.fields[3].value(), "sale price")?,
+                parse_date(self.fields[5].value(), "sale date")?,
+                false,
+            ),
+        };
+        let currency = parse_currency(self.fields[6].value())?;
+        let (stop_price, target_price) = self.plan()?;
+        if stop_price.is_some_and(|stop| stop >= cost) {
+            return Err("Stop must be below the cost/share".into());
+        }
+        if target_price.is_some_and(|target| target <= cost) {
+            return Err("Target must be above the cost/share".into());
+        }
 
         if sale_date < purchase_date {
             return Err("Sale date cannot be before purchase date".into());
         }
+        if option.is_some_and(|contract| sale_date > contract.expiry) {
+            return Err("Sale date cannot be after the option's expiry".into());
+        }
 
-        Ok(Position {
-            ticker,
-            cost_per_share: cost,
-            quantity: qty,
-            sale_price,
-            purchase_date,
-            sale_date,
-        })
+        let mut pos = self.source.clone().unwrap_or_default();
+        // A different symbol gets its contract terms again from the registry.
+        if pos.ticker != ticker {
+            pos.instrument = Instrument::Equity;
+            pos.multiplier = 1.0;
+        }
+        pos.set_symbol(ticker, option);
+        pos.cost_per_share = cost;
+        pos.quantity = qty;
+        pos.sale_price = sale_price;
+        pos.purchase_date = purchase_date;
+        pos.sale_date = sale_date;
+        pos.currency = currency;
+        pos.stop_price = stop_price;
+        pos.target_price = target_price;
+        // The form holds entered values; splits are applied again on save.
+        pos.entered = None;
+        pos.open = open;
+        Ok(pos)
     }
 }
 
 #[derive(Clone)]
 struct ImportForm {
-    path: String,
+    path: TextInput,
     message: Option<String>,
     error: Option<String>,
+    /// Files and directories matching `path`, or recent imports while it is empty.
+    entries: Vec<PathEntry>,
+    /// Highlighted entry; `None` while the typed path has focus.
+    cursor: Option<usize>,
+    recent: Vec<String>,
+    /// Account the imported positions are assigned to; blank for none.
+    account: TextInput,
+    account_focused: bool,
 }
 
 impl ImportForm {
-    fn new() -> Self {
-        Self {
-            path: String::new(),
+    fn new(recent: &[String]) -> Self {
+        let mut form = Self {
+            path: TextInput::new(InputKind::Text),
             message: None,
             error: None,
+            entries: Vec::new(),
+            cursor: None,
+            recent: recent.to_vec(),
+            account: TextInput::new(InputKind::Text),
+            account_focused: false,
+        };
+        form.refresh();
+        form
+    }
+
+    /// Replaces the account with the next known one, wrapping to blank.
+    fn cycle_account(&mut self, accounts: &[String]) {
+        let next = match accounts.iter().position(|a| a == self.account.value()) {
+            Some(i) if i + 1 < accounts.len() => accounts[i + 1].clone(),
+            Some(_) => String::new(),
+            None => accounts.first().cloned().unwrap_or_default(),
+        };
+        self.account.set_value(next);
+    }
+
+    /// Passes an editing key to the path; the list follows the new text.
+    fn handle_key(&mut self, key: event::KeyEvent) {
+        let before = self.path.value().to_string();
+        self.path.handle_key(key);
+        if self.path.value() != before {
+            self.refresh();
+        }
+    }
+
+    fn paste(&mut self, text: &str) {
+        self.path.insert_str(text);
+        self.refresh();
+    }
+
+    fn showing_recent(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    fn refresh(&mut self) {
+        self.cursor = None;
+        self.entries = if self.showing_recent() {
+            self.recent
+                .iter()
+                .map(|p| PathEntry {
+                    name: p.clone(),
+                    path: p.clone(),
+                    is_dir: false,
+                })
+                .collect()
+        } else {
+            matching_entries(self.path.value())
+        };
+    }
+
+    /// Completes the typed path, or takes the highlighted entry.
+    fn complete(&mut self) {
+        match self.cursor {
+            Some(i) => self.path.set_value(self.entries[i].path.clone()),
+            None => match complete(self.path.value()) {
+                Some(completed) => self.path.set_value(completed),
+                None => {
+                    self.error = Some("No matching files or directories".into());
+                    return;
+                }
+            },
         }
+        self.error = None;
+        self.refresh();
     }
 
-    fn backspace(&mut self) {
-        self.path.pop();
+    fn select_next(&mut self) {
+        if !self.entries.is_empty() {
+            self.cursor = match self.cursor {
+                Some(i) if i + 1 < self.entries.len() => Some(i + 1),
+                Some(i) => Some(i),
+                None => Some(0),
+            };
+        }
+    }
+
+    /// Moves up the list; above the first entry, focus returns to the path.
+    fn select_prev(&mut self) {
+        self.cursor = match self.cursor {
+            Some(0) | None => None,
+            Some(i) => Some(i - 1),
+        };
     }
 
-    fn push_char(&mut self, c: char) {
-        self.path.push(c);
+    /// Handles Enter: opens a highlighted directory, or returns the path to
+    /// preview, with `~` and variables expanded.
+    fn choose(&mut self) -> Option<String> {
+        if let Some(i) = self.cursor {
+            let entry = self.entries[i].clone();
+            self.path.set_value(entry.path);
+            self.refresh();
+            if entry.is_dir {
+                return None;
+            }
+        }
+        Some(expand_path(self.path.value()))
     }
 }
 
-fn parse_f64(raw: &str, label: &str) -> Result<f64, String> {
-    parse_number(raw).ok_or_else(|| format!("Invalid {label}"))
+/// Worksheets of a workbook being imported; the chosen one is previewed.
+struct SheetPicker {
+    path: String,
+    sheets: Vec<String>,
+    cursor: usize,
 }
 
-fn parse_date(raw: &str, label: &str) -> Result<NaiveDate, String> {
-    parse_date_any(raw).map_err(|_| format!("Invalid {label}, expected YYYY-MM-DD or MM/DD/YYYY"))
+/// Parsed rows waiting for confirmation; nothing is saved until `commit_import`.
+struct ImportPreview {
+    path: String,
+    report: ImportReport,
+    cursor: usize,
+    policy: DuplicatePolicy,
+    account: Option<String>,
+    /// Whether the file's dividends go into the ledger with the rows.
+    include_dividends: bool,
 }
 
-fn parse_ticker(raw: &str) -> Result<String, String> {
+impl ImportPreview {
+    fn select_next(&mut self) {
+        if !self.report.rows.is_empty() {
+            self.cursor = (self.cursor + 1) % self.report.rows.len();
+        }
+    }
+
+    fn select_prev(&mut self) {
+        if self.report.rows.is_empty() {
+            return;
+        }
+        if self.cursor == 0 {
+            self.cursor = self.report.rows.len() - 1;
+        } else {
+            self.cursor -= 1;
+        }
+    }
+
+    fn toggle_current(&mut self) {
+        if let Some(row) = self.report.rows.get_mut(self.cursor) {
+            row.selected = !row.selected;
+        }
+    }
+
+    fn toggle_all(&mut self) {
+        let select = self.report.rows.iter().any(|r| !r.selected)
+            || (self.report.rows.is_empty() && !self.include_dividends);
+        for row in &mut self.report.rows {
+            row.selected = select;
+        }
+        self.include_dividends = select;
+    }
+}
+
+/// Fixed decimals, or as many as the value needs so nothing is cut off.
+fn format_exact(value: f64, decimals: usize) -> String {
+    let fixed = format!("{value:.decimals$}");
+    if fixed.parse::<f64>() == Ok(value) {
+        fixed
+    } else {
+        value.to_string()
+    }
+}
+
+fn parse_f64(raw: &str, label: &str) -> Result<f64, String> {
+    parse_number(raw).ok_or_else(|| format!("Invalid {label}"))
+}
+
+fn parse_date(raw: &str, label: &str) -> Result<NaiveDate, String> {
+    parse_date_any(raw).map_err(|_| format!("Invalid {label}, expected YYYY-MM-DD or MM/DD/YYYY"))
+}
+
+fn parse_ticker(raw: &str) -> Result<String, String> {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return Err("Ticker cannot be empty".into());
@@ -434,6 +1303,24 @@ fn parse_ticker(raw: &str) -> Result<String, String> {
     Ok(trimmed.to_ascii_uppercase())
 }
 
+/// Three-letter ISO code, uppercased; blank means the base currency.
+fn parse_currency(raw: &str) -> Result<Option<String>, String> {
+    let trimmed = raw.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    if trimmed.len() != 3 || !trimmed.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err("Currency must be a 3-letter code like USD or GBP".into());
+    }
+    Ok(Some(trimmed.to_ascii_uppercase()))
+}
+
+/// Account names are kept as typed; blank means no account.
+fn parse_account(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
 fn parse_date_any(raw: &str) -> Result<NaiveDate, ()> {
     let trimmed = raw.trim();
     NaiveDate::parse_from_str(trimmed, "%Y-%m-%d")
@@ -456,285 +1343,53 @@ fn parse_number(raw: &str) -> Option<f64> {
     cleaned.parse::<f64>().ok()
 }
 
-fn parse_positions_csv(path: &str) -> Result<Vec<Position>, String> {
-    let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
-
-    #[derive(Clone, Copy)]
-    struct HeaderIdx {
-        ticker: usize,
-        cost: usize,
-        qty: usize,
-        sale_price: usize,
-        buy_date: usize,
-        sale_date: usize,
-    }
-
-    fn sanitize_header(s: &str) -> String {
-        s.chars()
-            .filter(|c| c.is_ascii_alphanumeric())
-            .flat_map(|c| c.to_lowercase())
-            .collect()
-    }
-
-    fn detect_header(parts: &[String]) -> Option<HeaderIdx> {
-        let mut t = None;
-        let mut cost = None;
-        let mut qty = None;
-        let mut sale = None;
-        let mut buy_d = None;
-        let mut sale_d = None;
-        let mut date_cols: Vec<usize> = Vec::new();
-
-        for (i, raw) in parts.iter().enumerate() {
-            let h = sanitize_header(raw);
-            match h.as_str() {
-                "symbol" | "ticker" => t = Some(i),
-                "qty" | "qtynumber" | "qtyshare" | "quantity" | "qtyshares" => qty = Some(i),
-                "costshare" | "costpershare" => cost = Some(i),
-                "priceshare" | "pricepershare" | "saleprice" | "sellprice" => sale = Some(i),
-                "dateadded" | "purchasedate" | "buydate" => buy_d = Some(i),
-                "date" | "saledate" | "selldate" => date_cols.push(i),
-                _ => {}
-            }
-        }
-
-        if buy_d.is_none()
-            && let Some(&first_date) = date_cols.first()
-        {
-            buy_d = Some(first_date);
-        }
-        if sale_d.is_none() {
-            if let Some(second_date) = date_cols.get(1) {
-                sale_d = Some(*second_date);
-            } else if let Some(&first_date) = date_cols.first() {
-                sale_d = Some(first_date);
-            }
-        }
-
-        match (t, cost, qty, sale, buy_d, sale_d) {
-            (Some(t), Some(c), Some(q), Some(s), Some(bd), Some(sd)) => Some(HeaderIdx {
-                ticker: t,
-                cost: c,
-                qty: q,
-                sale_price: s,
-                buy_date: bd,
-                sale_date: sd,
-            }),
-            _ => None,
-        }
-    }
-
-    let mut rdr = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .trim(Trim::All)
-        .flexible(true)
-        .from_reader(data.as_bytes());
-
-    let mut header_idx: Option<HeaderIdx> = None;
-    let mut positions = Vec::new();
-    let mut in_details_section = false;
-    let mut current_ticker: Option<String> = None;
-
-    for (idx, result) in rdr.records().enumerate() {
-        let line_no = idx + 1;
-        let record = result.map_err(|e| format!("Line {line_no}: {e}"))?;
-        if record.is_empty() {
-            continue;
-        }
-
-        let fields: Vec<String> = record.iter().map(|s| s.to_string()).collect();
-        let joined_lower = fields.join(" ").to_ascii_lowercase();
-        if joined_lower.contains("taxable g&l details") {
-            in_details_section = true;
-            header_idx = None;
-            continue;
-        }
-
-        // Skip anything before we reach the TAXABLE G&L DETAILS table.
-        if !in_details_section && header_idx.is_none() {
-            continue;
-        }
-
-        // Skip summary/total lines but keep headers that include the word "Total"
-        if fields.len() == 1 {
-            let first = fields[0].trim().to_ascii_lowercase();
-            if first.contains("total") || first.contains("subtotal") {
-                continue;
-            }
-        }
-        if let Some(first) = fields.first() {
-            let first_lower = first.trim().to_ascii_lowercase();
-            if first_lower == "total" || first_lower == "subtotal" {
-                continue;
-            }
-        }
-
-        if header_idx.is_none() {
-            if let Some(h) = detect_header(&fields) {
-                header_idx = Some(h);
-                continue;
-            }
-            // Not a header row; ignore until we find one.
-            continue;
-        }
-
-        let get = |i: usize| fields.get(i).map(|s| s.as_str()).unwrap_or("");
-
-        let push_position = |ticker: String,
-                             cost: f64,
-                             qty: f64,
-                             sale_price: f64,
-                             purchase_date: NaiveDate,
-                             sale_date: NaiveDate,
-                             positions: &mut Vec<Position>| {
-            positions.push(Position {
-                ticker,
-                cost_per_share: cost,
-                quantity: qty,
-                sale_price,
-                purchase_date,
-                sale_date,
-            });
-        };
-
-        if let Some(h) = header_idx {
-            let raw_ticker = get(h.ticker).trim();
-            // Update current ticker when we see a non-sell summary row, even if numbers are missing.
-            if !raw_ticker.is_empty()
-                && raw_ticker != "--"
-                && !raw_ticker.to_ascii_lowercase().starts_with("sell")
-            {
-                let parsed =
-                    parse_ticker(raw_ticker).map_err(|e| format!("Line {line_no}: {e}"))?;
-                current_ticker = Some(parsed);
-            }
-
-            let required_missing = |i: usize| {
-                let v = get(i).trim();
-                v.is_empty() || v == "--"
-            };
-            if required_missing(h.cost)
-                || required_missing(h.qty)
-                || required_missing(h.sale_price)
-                || required_missing(h.buy_date)
-                || required_missing(h.sale_date)
-            {
-                continue;
-            }
-
-            let ticker = if let Some(t) = &current_ticker {
-                t.clone()
-            } else {
-                continue; // no context yet
-            };
-            let cost =
-                parse_f64(get(h.cost), "cost/share").map_err(|e| format!("Line {line_no}: {e}"))?;
-            let qty =
-                parse_f64(get(h.qty), "quantity").map_err(|e| format!("Line {line_no}: {e}"))?;
-            let sale_price = parse_f64(get(h.sale_price), "sale price")
-                .map_err(|e| format!("Line {line_no}: {e}"))?;
-            let purchase_date = parse_date(get(h.buy_date), "purchase date")
-                .map_err(|e| format!("Line {line_no}: {e}"))?;
-            let sale_date = parse_date(get(h.sale_date), "sale date")
-                .map_err(|e| format!("Line {line_no}: {e}"))?;
-
-            if sale_date < purchase_date {
-                return Err(format!(
-                    "Line {line_no}: sale date cannot be before purchase date"
-                ));
-            }
-
-            push_position(
-                ticker,
-                cost,
-                qty,
-                sale_price,
-                purchase_date,
-                sale_date,
-                &mut positions,
-            );
-            continue;
-        }
-
-        // Fallback: expect at least 6 columns in ticker,cost,qty,sale,purchase_date,sale_date order
-        if fields.len() < 6 {
-            // pre/post table fluff; skip
-            continue;
-        }
-
-        let raw_ticker = get(0).trim();
-        // Update current ticker from summary rows, skip adding a position for them
-        if !raw_ticker.is_empty()
-            && raw_ticker != "--"
-            && !raw_ticker.to_ascii_lowercase().starts_with("sell")
-        {
-            let parsed = parse_ticker(raw_ticker).map_err(|e| format!("Line {line_no}: {e}"))?;
-            current_ticker = Some(parsed);
-            continue;
-        }
-
-        let required_missing = |s: &str| {
-            let t = s.trim();
-            t.is_empty() || t == "--"
-        };
-        if required_missing(get(1))
-            || required_missing(get(2))
-            || required_missing(get(3))
-            || required_missing(get(4))
-            || required_missing(get(5))
-        {
-            continue;
-        }
-
-        let ticker = if let Some(t) = &current_ticker {
-            t.clone()
-        } else {
-            continue;
-        };
-        let cost = parse_f64(get(1), "cost/share").map_err(|e| format!("Line {line_no}: {e}"))?;
-        let qty = parse_f64(get(2), "quantity").map_err(|e| format!("Line {line_no}: {e}"))?;
-        let sale_price =
-            parse_f64(get(3), "sale price").map_err(|e| format!("Line {line_no}: {e}"))?;
-        let purchase_date =
-            parse_date(get(4), "purchase date").map_err(|e| format!("Line {line_no}: {e}"))?;
-        let sale_date =
-            parse_date(get(5), "sale date").map_err(|e| format!("Line {line_no}: {e}"))?;
-
-        if sale_date < purchase_date {
-            return Err(format!(
-                "Line {line_no}: sale date cannot be before purchase date"
-            ));
-        }
+fn file_extension(path: &str) -> String {
+    Path::new(path)
+        .extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .unwrap_or_default()
+}
 
-        push_position(
-            ticker,
-            cost,
-            qty,
-            sale_price,
-            purchase_date,
-            sale_date,
-            &mut positions,
-        );
-    }
+/// Everything stored in the data file.
+#[derive(Default, Serialize, Deserialize)]
+struct DataFile {
+    positions: Vec<Position>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    trash: Vec<TrashEntry>,
+    /// Corporate actions applied to the positions' quantities and prices.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    splits: Vec<Split>,
+    /// Cash distributions, attributed to positions held over the ex-date.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dividends: Vec<Dividend>,
+}
 
-    if positions.is_empty() {
-        return Err("No rows found to import".into());
-    }
-    Ok(positions)
+/// Data files written before the trash existed are a bare array of positions.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredData {
+    Current(DataFile),
+    Legacy(Vec<Position>),
 }
 
-fn load_positions() -> Result<Vec<Position>, String> {
+fn load_data() -> Result<DataFile, String> {
     let path = Path::new(DATA_FILE);
     if !path.exists() {
         return Err("no data file".into());
     }
     let data = fs::read_to_string(path).map_err(|e| format!("Failed to read data file: {e}"))?;
-    serde_json::from_str(&data).map_err(|e| format!("Failed to parse data file: {e}"))
+    match serde_json::from_str(&data).map_err(|e| format!("Failed to parse data file: {e}"))? {
+        StoredData::Current(file) => Ok(file),
+        StoredData::Legacy(positions) => Ok(DataFile {
+            positions,
+            ..DataFile::default()
+        }),
+    }
 }
 
-fn save_positions(positions: &[Position]) {
+fn save_data(data: &DataFile) {
     let path = Path::new(DATA_FILE);
-    if let Ok(json) = serde_json::to_string_pretty(positions)
+    if let Ok(json) = serde_json::to_string_pretty(data)
         && let Err(err) = fs::write(path, json)
     {
         eprintln!("Could not save positions: {err}");
@@ -751,6 +1406,7 @@ fn seed_positions() -> Vec<Position> {
             sale_price: 127.5,
             purchase_date: today - chrono::Days::new(12),
             sale_date: today,
+            ..Default::default()
         },
         Position {
             ticker: "AMD".into(),
@@ -759,6 +1415,7 @@ fn seed_positions() -> Vec<Position> {
             sale_price: 59.4,
             purchase_date: today - chrono::Days::new(4),
             sale_date: today,
+            ..Default::default()
         },
         Position {
             ticker: "MSFT".into(),
@@ -767,6 +1424,7 @@ fn seed_positions() -> Vec<Position> {
             sale_price: 355.2,
             purchase_date: today - chrono::Days::new(25),
             sale_date: today - chrono::Days::new(5),
+            ..Default::default()
         },
     ]
 }
@@ -777,6 +1435,19 @@ enum Mode {
     Detail,
     AddForm,
     Import,
+    ImportSheet,
+    ImportMapping,
+    ImportPreview,
+    ConfirmDelete,
+    BulkEdit,
+    ExDate,
+    Journal,
+    Tags,
+    Risk,
+    Sizing,
+    Splits,
+    Dividends,
+    Trash,
     Help,
 }
 
@@ -786,28 +1457,354 @@ struct App {
     mode: Mode,
     form: AddForm,
     import_form: ImportForm,
+    import_preview: Option<ImportPreview>,
+    sheet_picker: Option<SheetPicker>,
+    mapping_wizard: Option<MappingWizard>,
+    config: Config,
+    trash: Vec<TrashEntry>,
+    trash_selected: usize,
+    /// Positions awaiting delete confirmation, and the mode to return to.
+    pending_delete: Option<(Vec<usize>, Mode)>,
+    /// Indices into `positions` marked for bulk operations.
+    marked: BTreeSet<usize>,
+    /// Last row toggled with space; `V` marks from here to the selection.
+    mark_anchor: Option<usize>,
+    /// Summary rows cover only the marked positions.
+    summary_marked_only: bool,
+    bulk: BulkEdit,
+    sizing: SizingForm,
+    /// Tags and notes being edited for the selected position.
+    journal: Option<JournalForm>,
+    /// Account shown in the portfolio; `None` shows all of them.
+    account: Option<String>,
+    /// Account chosen in the import dialog for the file being previewed.
+    import_account: Option<String>,
+    splits: Vec<Split>,
+    splits_selected: usize,
+    /// Result of the last splits import, shown in the splits view.
+    splits_message: Option<String>,
+    dividends: Vec<Dividend>,
+    dividends_selected: usize,
+    /// Ex-date being typed for the selected payment.
+    ex_date_input: TextInput,
+    ex_date_error: Option<String>,
+    fx: FxRates,
+    /// Why the FX rates file could not be read, shown in the header.
+    fx_error: Option<String>,
+    instruments: InstrumentRegistry,
+    /// Why the instrument registry could not be read, shown in the header.
+    instruments_error: Option<String>,
+    prices: PriceStore,
+    /// Symbols whose price files `prices` was read for.
+    price_symbols: Vec<String>,
+    quote_cache: QuoteCache,
+    /// The quote refresh running in the background, if any.
+    refreshing: Option<Receiver<Refreshed>>,
+    last_refresh: Option<Instant>,
+    /// Outcome of the last quote refresh, shown in the header.
+    quote_status: Option<String>,
+    /// Why the quote cache could not be read, shown in the header until the
+    /// next refresh saves a new one.
+    quote_cache_error: Option<String>,
+    /// Why the settings file could not be read; while set, settings are
+    /// not saved over it.
+    config_error: Option<String>,
     editing: Option<usize>,
     filter_text: String,
     filter_editing: bool,
 }
 
 impl App {
+    /// The stored data, or demo positions when there is none yet.
     fn new() -> Self {
-        let positions = load_positions().unwrap_or_else(|_| seed_positions());
+        Self::with_data(load_data().unwrap_or_else(|_| DataFile {
+            positions: seed_positions(),
+            ..DataFile::default()
+        }))
+    }
+
+    fn with_data(data: DataFile) -> Self {
+        let DataFile {
+            mut positions,
+            mut trash,
+            splits,
+            dividends,
+        } = data;
+        apply_splits(&mut positions, &splits);
+        let (config, config_error) = match load_config() {
+            Ok(config) => (config, None),
+            Err(err) => (Config::default(), Some(err)),
+        };
+        let purged = purge_expired(&mut trash, config.trash_retention_days, chrono::Utc::now());
+        let (fx, fx_error) = match FxRates::load(&config.fx_rates_file, &config.base_currency) {
+            Ok(fx) => (fx, None),
+            Err(err) => (FxRates::empty(&config.base_currency), Some(err)),
+        };
+        let (instruments, instruments_error) =
+            match InstrumentRegistry::load(&config.instruments_file) {
+                Ok(registry) => (registry, None),
+                Err(err) => (InstrumentRegistry::builtin(), Some(err)),
+            };
         let selected = if positions.is_empty() {
             0
         } else {
             positions.len() - 1
         };
-        Self {
+        // An unreadable cache only costs a refetch.
+        let (quote_cache, quote_cache_error) = match QuoteCache::load(&config.quote_cache_file) {
+            Ok(cache) => (cache, None),
+            Err(err) => (QuoteCache::default(), Some(err)),
+        };
+        let mut app = Self {
             positions,
             selected,
             mode: Mode::Portfolio,
             form: AddForm::new(),
-            import_form: ImportForm::new(),
+            import_form: ImportForm::new(&config.recent_imports),
+            import_preview: None,
+            sheet_picker: None,
+            mapping_wizard: None,
+            config,
+            trash,
+            trash_selected: 0,
+            pending_delete: None,
+            marked: BTreeSet::new(),
+            mark_anchor: None,
+            summary_marked_only: false,
+            bulk: BulkEdit::new(),
+            sizing: SizingForm::new(0.0),
+            journal: None,
+            account: None,
+            import_account: None,
+            splits,
+            splits_selected: 0,
+            splits_message: None,
+            dividends,
+            dividends_selected: 0,
+            ex_date_input: TextInput::new(InputKind::Date),
+            ex_date_error: None,
+            fx,
+            fx_error,
+            instruments,
+            instruments_error,
+            prices: PriceStore::default(),
+            price_symbols: Vec::new(),
+            quote_cache,
+            refreshing: None,
+            last_refresh: None,
+            quote_status: None,
+            quote_cache_error,
+            config_error,
             editing: None,
             filter_text: String::new(),
             filter_editing: false,
+        };
+        app.refresh_prices();
+        app.refresh_income();
+        if purged > 0 {
+            app.save();
+        }
+        app
+    }
+
+    /// Re-applies split adjustments, then writes the data file.
+    fn save(&mut self) {
+        apply_splits(&mut self.positions, &self.splits);
+        self.mark_prices();
+        self.refresh_income();
+        save_data(&DataFile {
+            positions: self.positions.clone(),
+            trash: self.trash.clone(),
+            splits: self.splits.clone(),
+            dividends: self.dividends.clone(),
+        });
+    }
+
+    /// Every symbol held, plus the benchmark, sorted.
+    fn held_symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self
+            .positions
+            .iter()
+            .map(|p| p.symbol())
+            .chain(Some(self.config.benchmark.clone()).filter(|b| !b.is_empty()))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        symbols
+    }
+
+    /// Re-reads the price files for every symbol held and marks open
+    /// positions to their latest close.
+    fn refresh_prices(&mut self) {
+        self.price_symbols = self.held_symbols();
+        self.prices = PriceStore::load(
+            &self.config.price_history_dir,
+            self.price_symbols.iter().map(String::as_str),
+        );
+        for (symbol, cached) in &self.quote_cache.quotes {
+            self.prices.add_quote(symbol, cached.quote);
+        }
+        self.mark_prices();
+    }
+
+    /// Marks open positions to the prices already read, reading the files
+    /// again only when the symbols held have changed.
+    fn mark_prices(&mut self) {
+        if self.held_symbols() != self.price_symbols {
+            return self.refresh_prices();
+        }
+        mark_to_market(
+            &mut self.positions,
+            &self.prices,
+            chrono::Local::now().date_naive(),
+        );
+    }
+
+    /// Fetches quotes for the open positions on a background thread. Unless
+    /// forced, symbols fetched within the refresh interval are skipped.
+    fn start_refresh(&mut self, force: bool) {
+        if self.refreshing.is_some() {
+            return;
+        }
+        let max_age = Duration::from_secs(u64::from(self.config.quote_refresh_minutes) * 60);
+        let now = chrono::Utc::now();
+        let mut symbols: Vec<String> = self
+            .positions
+            .iter()
+            .filter(|p| p.open)
+            .map(|p| p.symbol())
+            .filter(|s| force || !self.quote_cache.is_fresh(s, now, max_age))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        self.last_refresh = Some(Instant::now());
+        if symbols.is_empty() {
+            return;
+        }
+        let source = self
+            .config
+            .price_source
+            .build(&self.config.price_history_dir);
+        self.refreshing = Some(spawn_refresh(source, symbols));
+        self.quote_status = Some("refreshing quotes…".into());
+    }
+
+    /// Picks up a finished refresh: caches the quotes and re-marks the
+    /// open positions. Starts the next one when the interval has passed.
+    fn poll_refresh(&mut self) {
+        let interval = u64::from(self.config.quote_refresh_minutes) * 60;
+        if interval > 0
+            && self
+                .last_refresh
+                .is_none_or(|t| t.elapsed() >= Duration::from_secs(interval))
+        {
+            self.start_refresh(false);
+        }
+        let Some(rx) = &self.refreshing else {
+            return;
+        };
+        let refreshed = match rx.try_recv() {
+            Ok(refreshed) => refreshed,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.refreshing = None;
+                self.quote_status = Some("quote refresh stopped".into());
+                return;
+            }
+        };
+        self.refreshing = None;
+        let now = chrono::Utc::now();
+        let updated = refreshed.quotes.len();
+        for (symbol, quote) in refreshed.quotes {
+            self.prices.add_quote(&symbol, quote);
+            self.quote_cache.insert(symbol, quote, now);
+        }
+        let mut status = format!("{updated} quotes updated");
+        if !refreshed.missing.is_empty() {
+            status.push_str(&format!(", none for {}", refreshed.missing.join(" ")));
+        }
+        let saved = self.quote_cache.save(&self.config.quote_cache_file);
+        if saved.is_ok() {
+            self.quote_cache_error = None;
+        }
+        for err in refreshed.errors.first().into_iter().chain(&saved.err()) {
+            status = format!("{status}; {err}");
+        }
+        self.quote_status = Some(status);
+        // Quotes live in the cache; positions.json is only written when
+        // the user changes something.
+        if updated > 0 {
+            self.mark_prices();
+            self.refresh_income();
+        }
+    }
+
+    /// Recomputes each position's dividend income; zero unless returns
+    /// include income.
+    fn refresh_income(&mut self) {
+        let income = if self.config.total_return {
+            attribute(&self.positions, &self.dividends)
+        } else {
+            vec![0.0; self.positions.len()]
+        };
+        for (pos, income) in self.positions.iter_mut().zip(income) {
+            pos.income = income;
+        }
+    }
+
+    /// Writes the settings, unless the file on disk could not be read:
+    /// saving would replace it with the defaults.
+    fn save_settings(&self) {
+        if self.config_error.is_none() {
+            save_config(&self.config);
+        }
+    }
+
+    fn toggle_total_return(&mut self) {
+        self.config.total_return = !self.config.total_return;
+        self.save_settings();
+        self.refresh_income();
+    }
+
+    /// Opens the ex-date editor on the selected payment.
+    fn edit_ex_date(&mut self) {
+        let Some(dividend) = self.dividends.get(self.dividends_selected) else {
+            return;
+        };
+        let current = dividend.ex_date.map(|d| d.format(DATE_FMT).to_string());
+        self.ex_date_input = TextInput::new(InputKind::Date);
+        self.ex_date_input.set_value(current.unwrap_or_default());
+        self.ex_date_error = None;
+        self.mode = Mode::ExDate;
+    }
+
+    /// Sets the typed ex-date on the selected payment; blank clears it.
+    fn set_ex_date(&mut self) -> Result<(), String> {
+        let raw = self.ex_date_input.value().trim();
+        let ex_date = if raw.is_empty() {
+            None
+        } else {
+            Some(parse_date(raw, "ex-date")?)
+        };
+        let dividend = self
+            .dividends
+            .get_mut(self.dividends_selected)
+            .ok_or("No payment selected")?;
+        if ex_date.is_some_and(|d| d > dividend.pay_date) {
+            return Err("Ex-date cannot be after the pay date".into());
+        }
+        dividend.ex_date = ex_date;
+        self.save();
+        Ok(())
+    }
+
+    fn delete_dividend(&mut self) {
+        if self.dividends_selected < self.dividends.len() {
+            self.dividends.remove(self.dividends_selected);
+            self.dividends_selected = self
+                .dividends_selected
+                .min(self.dividends.len().saturating_sub(1));
+            self.save();
         }
     }
 
@@ -847,11 +1844,38 @@ impl App {
         self.positions.get(self.selected)
     }
 
+    /// Asks for confirmation before deleting the marked positions, or the
+    /// selected one when nothing is marked.
+    fn request_delete(&mut self) {
+        let targets: Vec<usize> = if self.marked.is_empty() {
+            self.selected_position()
+                .map(|_| self.selected)
+                .into_iter()
+                .collect()
+        } else {
+            self.marked.iter().copied().collect()
+        };
+        if !targets.is_empty() {
+            self.pending_delete = Some((targets, self.mode));
+            self.mode = Mode::ConfirmDelete;
+        }
+    }
+
+    /// Moves the positions awaiting confirmation to the trash.
     fn delete_selected(&mut self) {
-        if self.positions.is_empty() {
+        let Some((mut targets, _)) = self.pending_delete.take() else {
             return;
+        };
+        // Remove from the back so earlier indices stay valid.
+        targets.sort_unstable_by(|a, b| b.cmp(a));
+        for idx in targets {
+            if idx < self.positions.len() {
+                let position = self.positions.remove(idx);
+                self.trash.push(TrashEntry::new(position));
+            }
         }
-        self.positions.remove(self.selected);
+        self.marked.clear();
+        self.mark_anchor = None;
         if self.positions.is_empty() {
             self.selected = 0;
         } else if self.selected >= self.positions.len() {
@@ -860,83 +1884,486 @@ impl App {
         if !self.positions.is_empty() {
             self.ensure_selection_visible();
         }
-        save_positions(&self.positions);
+        self.save();
     }
 
-    fn import_csv(&mut self, path: &str) -> Result<usize, String> {
-        let start = self.positions.len();
-        let new_positions = parse_positions_csv(path)?;
-        self.positions.extend(new_positions);
-        if !self.positions.is_empty() {
-            self.selected = self.positions.len() - 1;
+    fn toggle_mark(&mut self) {
+        if self.selected_position().is_none() {
+            return;
         }
-        self.ensure_selection_visible();
-        save_positions(&self.positions);
-        Ok(self.positions.len() - start)
+        if !self.marked.remove(&self.selected) {
+            self.marked.insert(self.selected);
+        }
+        self.mark_anchor = Some(self.selected);
     }
 
-    fn filter_matches(&self, pos: &Position) -> bool {
-        if self.filter_text.is_empty() {
-            return true;
-        }
-        let needle = self.filter_text.to_ascii_uppercase();
-        pos.ticker.to_ascii_uppercase().contains(&needle)
+    /// Marks every visible row between the anchor and the selection.
+    fn mark_range(&mut self) {
+        let filtered = self.filtered_indices();
+        let Some(to) = filtered.iter().position(|&i| i == self.selected) else {
+            return;
+        };
+        let from = self
+            .mark_anchor
+            .and_then(|a| filtered.iter().position(|&i| i == a))
+            .unwrap_or(to);
+        let (lo, hi) = if from <= to { (from, to) } else { (to, from) };
+        self.marked.extend(&filtered[lo..=hi]);
+        self.mark_anchor = Some(self.selected);
     }
 
-    fn filtered_positions(&self) -> Vec<(usize, &Position)> {
-        self.positions
-            .iter()
-            .enumerate()
-            .filter(|(_, p)| self.filter_matches(p))
-            .collect()
+    /// Marks all rows matching the filter, or unmarks them if they already are.
+    fn mark_all_filtered(&mut self) {
+        let filtered = self.filtered_indices();
+        if filtered.iter().all(|i| self.marked.contains(i)) {
+            for i in &filtered {
+                self.marked.remove(i);
+            }
+        } else {
+            self.marked.extend(filtered);
+        }
     }
 
-    fn filtered_indices(&self) -> Vec<usize> {
-        self.filtered_positions()
-            .into_iter()
-            .map(|(i, _)| i)
-            .collect()
+    /// Every position restated in the base currency, in the same order.
+    fn base_positions(&self) -> Vec<Position> {
+        self.positions.iter().map(|p| self.fx.to_base(p)).collect()
     }
 
-    fn ensure_selection_visible(&mut self) {
-        let filtered = self.filtered_indices();
-        if filtered.is_empty() {
-            self.selected = 0;
-            return;
-        }
-        if !filtered.contains(&self.selected) {
-            self.selected = filtered[0];
-        }
+    /// MAE and MFE from the symbol's price history, when there is one.
+    fn excursion(&self, pos: &Position) -> Option<Excursion> {
+        self.prices
+            .get(&pos.symbol())
+            .and_then(|history| excursion(pos, history))
     }
-}
 
-fn ui(f: &mut Frame, app: &App) {
-    let size = f.size();
-    let vertical = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Min(10),
-            Constraint::Length(3),
-        ])
-        .split(size);
+    /// Risk figures for the shown positions, in the base currency.
+    fn risk(&self) -> RiskMetrics {
+        let base = self.base_positions();
+        let shown: Vec<&Position> = self.filtered_indices().iter().map(|&i| &base[i]).collect();
+        risk_metrics(&daily_returns(&shown), self.config.risk_free_rate)
+    }
 
-    draw_header(f, vertical[0], app);
+    /// Taxable realized gains of the shown positions, in the base currency,
+    /// leaving out tax-advantaged accounts.
+    fn taxable_gains(&self) -> TaxableGains {
+        let base = self.base_positions();
+        let shown: Vec<&Position> = self.filtered_indices().iter().map(|&i| &base[i]).collect();
+        taxable_gains(&shown, |account| self.config.is_tax_advantaged(account))
+    }
 
-    match app.mode {
-        Mode::Portfolio => draw_portfolio(f, vertical[1], app),
-        Mode::Detail => draw_detail(f, vertical[1], app),
-        Mode::AddForm => draw_form(f, size, app),
-        Mode::Import => draw_import_form(f, size, app),
-        Mode::Help => draw_help(f, size),
+    /// Starting balance plus the realized PnL of the account shown, in the
+    /// base currency.
+    fn account_equity(&self) -> f64 {
+        let realized: f64 = self
+            .positions
+            .iter()
+            .filter(|p| !p.open)
+            .filter(|p| self.account.is_none() || p.account == self.account)
+            .map(|p| self.fx.to_base(p).roi_value())
+            .sum();
+        self.config.starting_balance + realized
     }
 
-    draw_footer(f, vertical[2], app.mode);
-}
+    /// Sizes the trade in the calculator, using the contract size and
+    /// quantity decimals of the ticker typed in it.
+    fn size_position(&self) -> Result<Sizing, String> {
+        let mut pos = Position::default();
+        if let Ok((ticker, option)) = parse_symbol(self.sizing.value(0)) {
+            pos.set_symbol(ticker, option);
+            self.instruments.classify(&mut pos);
+        }
+        self.sizing
+            .compute(pos.multiplier, self.instruments.quantity_decimals(&pos))
+    }
+
+    /// R-multiples of the shown closed positions that have a stop.
+    fn r_stats(&self) -> RStats {
+        let rs: Vec<f64> = self
+            .filtered_positions()
+            .into_iter()
+            .filter(|(_, p)| !p.open)
+            .filter_map(|(_, p)| p.r_multiple())
+            .collect();
+        r_stats(&rs)
+    }
+
+    /// The benchmark's price history, when one is set and its file exists.
+    fn benchmark(&self) -> Option<&PriceHistory> {
+        Some(self.config.benchmark.as_str())
+            .filter(|b| !b.is_empty())
+            .and_then(|b| self.prices.get(b))
+    }
+
+    /// The position's return next to the benchmark's over its holding period.
+    fn comparison(&self, pos: &Position) -> Option<Comparison> {
+        self.benchmark().and_then(|history| compare(pos, history))
+    }
+
+    /// Formats an amount in a position's own currency.
+    fn money(&self, value: f64, pos: &Position) -> String {
+        format_money(value, self.fx.currency_of(pos))
+    }
+
+    /// Formats a quantity with the decimals its instrument needs.
+    fn quantity(&self, pos: &Position) -> String {
+        let decimals = self.instruments.quantity_decimals(pos);
+        format!("{:.decimals$}", pos.quantity)
+    }
+
+    /// Formats an amount in the base currency.
+    fn base_money(&self, value: f64) -> String {
+        format_money(value, &self.fx.base)
+    }
+
+    /// Account names in use, sorted.
+    fn accounts(&self) -> Vec<String> {
+        let names: BTreeSet<&str> = self
+            .positions
+            .iter()
+            .filter_map(|p| p.account.as_deref())
+            .collect();
+        names.into_iter().map(str::to_string).collect()
+    }
+
+    /// Steps the header's account switcher through "All" and each account.
+    fn switch_account(&mut self, forward: bool) {
+        let mut views: Vec<Option<String>> = vec![None];
+        views.extend(self.accounts().into_iter().map(Some));
+        let current = views.iter().position(|v| *v == self.account).unwrap_or(0);
+        let n = views.len();
+        let next = if forward {
+            (current + 1) % n
+        } else {
+            (current + n - 1) % n
+        };
+        self.account = views.swap_remove(next);
+        self.marked.clear();
+        self.mark_anchor = None;
+        self.ensure_selection_visible();
+    }
+
+    /// Opens the import dialog, importing into the account being viewed.
+    fn open_import_form(&mut self) {
+        self.mode = Mode::Import;
+        self.import_form = ImportForm::new(&self.config.recent_imports);
+        if let Some(account) = &self.account {
+            self.import_form.account.set_value(account.clone());
+        }
+    }
+
+    fn open_trash(&mut self) {
+        self.trash_selected = self.trash.len().saturating_sub(1);
+        self.mode = Mode::Trash;
+    }
+
+    fn restore_from_trash(&mut self) {
+        if self.trash_selected >= self.trash.len() {
+            return;
+        }
+        let entry = self.trash.remove(self.trash_selected);
+        self.positions.push(entry.position);
+        self.selected = self.positions.len() - 1;
+        self.trash_selected = self.trash_selected.min(self.trash.len().saturating_sub(1));
+        self.ensure_selection_visible();
+        self.save();
+    }
+
+    fn purge_from_trash(&mut self) {
+        if self.trash_selected >= self.trash.len() {
+            return;
+        }
+        self.trash.remove(self.trash_selected);
+        self.trash_selected = self.trash_selected.min(self.trash.len().saturating_sub(1));
+        self.save();
+    }
+
+    /// Parses `path` for the preview, or opens the mapping wizard when no
+    /// header row is recognized. Returns the mode to switch to.
+    fn preview_import(&mut self, path: &str) -> Result<Mode, String> {
+        if !Path::new(path).is_file() {
+            return Err(format!("No such file: {path}"));
+        }
+        let extension = file_extension(path);
+        match extension.as_str() {
+            "xml" => return self.show_import_preview(path, parse_flex_file(path)?),
+            "ofx" | "qfx" => {
+                let today = chrono::Local::now().date_naive();
+                return self.show_import_preview(path, ofx::parse_ofx_file(path, today)?);
+            }
+            ext if WORKBOOK_EXTENSIONS.contains(&ext) => {
+                let sheets = sheet_names(path)?;
+                if sheets.len() > 1 {
+                    self.sheet_picker = Some(SheetPicker {
+                        path: path.to_string(),
+                        sheets,
+                        cursor: 0,
+                    });
+                    return Ok(Mode::ImportSheet);
+                }
+                return self.preview_sheet(path, &sheets[0]);
+            }
+            _ => {}
+        }
+        let records = read_csv_records(path)?;
+        if let Some(splits) = parse_split_records(&records) {
+            return self.import_splits(splits?);
+        }
+        self.preview_records(
+            path,
+            SheetRecords {
+                records,
+                native: NativeCells::new(),
+            },
+        )
+    }
+
+    /// Adds splits from a splits CSV and shows the splits view.
+    fn import_splits(&mut self, splits: Vec<Split>) -> Result<Mode, String> {
+        let total = splits.len();
+        let added = merge_splits(&mut self.splits, splits);
+        self.save();
+        self.splits_message = Some(format!(
+            "Added {added} splits ({} already listed)",
+            total - added
+        ));
+        self.splits_selected = 0;
+        Ok(Mode::Splits)
+    }
+
+    fn delete_split(&mut self) {
+        if self.splits_selected < self.splits.len() {
+            let split = self.splits.remove(self.splits_selected);
+            self.splits_selected = self
+                .splits_selected
+                .min(self.splits.len().saturating_sub(1));
+            self.splits_message = Some(format!(
+                "Removed {} {} split of {}",
+                split.ticker,
+                split.describe(),
+                split.date.format(DATE_FMT)
+            ));
+            self.save();
+        }
+    }
+
+    fn preview_sheet(&mut self, path: &str, sheet: &str) -> Result<Mode, String> {
+        let records = read_sheet(path, sheet)?;
+        self.sheet_picker = None;
+        self.preview_records(path, records)
+    }
+
+    fn preview_records(&mut self, path: &str, sheet: SheetRecords) -> Result<Mode, String> {
+        let report = parse_import_records(
+            &sheet.records,
+            &sheet.native,
+            &self.config.import_profiles,
+            chrono::Local::now().date_naive(),
+        );
+        if report.mapping.is_none() {
+            if sheet.records.is_empty() {
+                return Err("No rows found to import".into());
+            }
+            self.mapping_wizard = Some(MappingWizard::new(path, sheet.records, sheet.native));
+            return Ok(Mode::ImportMapping);
+        }
+        self.show_import_preview(path, report)
+    }
+
+    /// Saves the wizard's mapping as a profile and previews the file with it.
+    fn apply_mapping_wizard(&mut self) -> Result<Mode, String> {
+        let Some(wizard) = &self.mapping_wizard else {
+            return Ok(Mode::Import);
+        };
+        let profile = wizard.build_profile()?;
+        let report = parse_position_records(
+            &wizard.records,
+            &wizard.native,
+            std::slice::from_ref(&profile),
+        );
+        let path = wizard.path.clone();
+        let mode = self.show_import_preview(&path, report)?;
+        self.config.upsert_profile(profile);
+        self.save_settings();
+        self.mapping_wizard = None;
+        Ok(mode)
+    }
+
+    fn show_import_preview(
+        &mut self,
+        path: &str,
+        mut report: ImportReport,
+    ) -> Result<Mode, String> {
+        if report.rows.is_empty() && report.skipped.is_empty() {
+            return Err("No rows found to import".into());
+        }
+        for row in &mut report.rows {
+            self.instruments.classify(&mut row.position);
+        }
+        report.classify(&self.positions);
+        self.import_preview = Some(ImportPreview {
+            path: path.to_string(),
+            report,
+            cursor: 0,
+            policy: DuplicatePolicy::Skip,
+            account: self.import_account.clone(),
+            include_dividends: true,
+        });
+        Ok(Mode::ImportPreview)
+    }
+
+    /// Applies the selected preview rows and returns a summary message.
+    fn commit_import(&mut self) -> String {
+        let Some(preview) = self.import_preview.take() else {
+            return "Nothing to import".into();
+        };
+        let (new, duplicate, changed) = preview.report.status_counts();
+        let mut dividends = if preview.include_dividends {
+            preview.report.dividends()
+        } else {
+            Vec::new()
+        };
+        for dividend in &mut dividends {
+            dividend.account = preview.account.clone();
+        }
+        let dividends_added = merge_dividends(&mut self.dividends, dividends);
+        let mut added = 0;
+        let mut replaced = 0;
+        let today = chrono::Local::now().date_naive();
+        for mut row in preview.report.rows.into_iter().filter(|r| r.selected) {
+            // The account chosen in the dialog wins over one read from the file.
+            if preview.account.is_some() {
+                row.position.account = preview.account.clone();
+            }
+            // Broker exports report quantities and prices on today's share basis.
+            row.position.adjusted_through = Some(today);
+            match (row.status, preview.policy) {
+                (MatchStatus::New, _) | (_, DuplicatePolicy::ImportAnyway) => {
+                    self.positions.push(row.position);
+                    added += 1;
+                }
+                (MatchStatus::Duplicate(i) | MatchStatus::Changed(i), DuplicatePolicy::Replace) => {
+                    self.positions[i].update_from_import(row.position);
+                    replaced += 1;
+                }
+                (_, DuplicatePolicy::Skip) => {}
+            }
+        }
+        if added > 0 {
+            self.selected = self.positions.len() - 1;
+        }
+        self.ensure_selection_visible();
+        if added + replaced + dividends_added > 0 {
+            self.save();
+        }
+        self.config.remember_import(&preview.path);
+        self.save_settings();
+        let mut message = format!(
+            "Imported {added}, replaced {replaced}: {new} new, {duplicate} duplicate, {changed} changed (matches: {})",
+            preview.policy.label()
+        );
+        if dividends_added > 0 {
+            message.push_str(&format!(", {dividends_added} dividends added"));
+        }
+        message
+    }
+
+    fn filter_matches(&self, pos: &Position) -> bool {
+        if self.account.is_some() && pos.account != self.account {
+            return false;
+        }
+        if self.filter_text.is_empty() {
+            return true;
+        }
+        let needle = self.filter_text.to_ascii_uppercase();
+        pos.ticker.to_ascii_uppercase().contains(&needle)
+    }
+
+    fn filtered_positions(&self) -> Vec<(usize, &Position)> {
+        self.positions
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| self.filter_matches(p))
+            .collect()
+    }
+
+    fn filtered_indices(&self) -> Vec<usize> {
+        self.filtered_positions()
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    fn ensure_selection_visible(&mut self) {
+        let filtered = self.filtered_indices();
+        if filtered.is_empty() {
+            self.selected = 0;
+            return;
+        }
+        if !filtered.contains(&self.selected) {
+            self.selected = filtered[0];
+        }
+    }
+}
+
+fn ui(f: &mut Frame, app: &App) {
+    let size = f.size();
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(3),
+        ])
+        .split(size);
+
+    draw_header(f, vertical[0], app);
+
+    match app.mode {
+        Mode::Portfolio => draw_portfolio(f, vertical[1], app),
+        Mode::Detail => draw_detail(f, vertical[1], app),
+        Mode::AddForm => draw_form(f, size, app),
+        Mode::Import => draw_import_form(f, size, app),
+        Mode::ImportSheet => draw_sheet_picker(f, size, app),
+        Mode::ImportMapping => draw_mapping_wizard(f, size, app),
+        Mode::ImportPreview => draw_import_preview(f, size, app),
+        Mode::ConfirmDelete => {
+            match app.pending_delete {
+                Some((_, Mode::Detail)) => draw_detail(f, vertical[1], app),
+                _ => draw_portfolio(f, vertical[1], app),
+            }
+            draw_confirm_delete(f, size, app);
+        }
+        Mode::BulkEdit => {
+            draw_portfolio(f, vertical[1], app);
+            draw_bulk_edit(f, size, app);
+        }
+        Mode::Journal => {
+            draw_detail(f, vertical[1], app);
+            draw_journal(f, size, app);
+        }
+        Mode::Tags => draw_tag_breakdown(f, vertical[1], app),
+        Mode::Risk => draw_risk(f, vertical[1], app),
+        Mode::Sizing => {
+            draw_portfolio(f, vertical[1], app);
+            draw_sizing(f, size, app);
+        }
+        Mode::Splits => draw_splits(f, vertical[1], app),
+        Mode::Dividends => draw_dividends(f, vertical[1], app),
+        Mode::ExDate => {
+            draw_dividends(f, vertical[1], app);
+            draw_ex_date(f, size, app);
+        }
+        Mode::Trash => draw_trash(f, vertical[1], app),
+        Mode::Help => draw_help(f, size),
+    }
+
+    draw_footer(f, vertical[2], app.mode);
+}
 
 fn draw_header(f: &mut Frame, area: Rect, app: &App) {
-    let (total_invested, total_proceeds, roi_pct) = portfolio_stats(&app.positions);
-    let title = Line::from(vec![
+    let base = app.base_positions();
+    let (total_invested, total_proceeds, roi_pct) = portfolio_stats(&base, app.account.as_deref());
+    let mut title = Line::from(vec![
         Span::styled(
             " ROI Tracker ",
             Style::default()
@@ -945,22 +2372,104 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
         ),
         Span::raw("  invested "),
         Span::styled(
-            format_currency(total_invested),
+            app.base_money(total_invested),
             Style::default().fg(Color::Yellow),
         ),
         Span::raw("  proceeds "),
         Span::styled(
-            format_currency(total_proceeds),
+            app.base_money(total_proceeds),
             Style::default().fg(Color::Green),
         ),
         Span::raw("  ROI "),
         styled_roi_pct(roi_pct),
     ]);
+    let accounts = app.accounts();
+    if app.account.is_none() && accounts.len() > 1 {
+        for account in &accounts {
+            let (_, _, roi) = portfolio_stats(&base, Some(account));
+            title.spans.push(Span::styled(
+                format!("  {account} "),
+                Style::default().fg(Color::Gray),
+            ));
+            title.spans.push(styled_roi_pct(roi));
+        }
+    }
+
+    let fx_issue = match &app.fx_error {
+        Some(err) => Some(err.clone()),
+        None => {
+            let missing = app.fx.missing(&app.positions);
+            (!missing.is_empty()).then(|| format!("no FX rate for {}", missing.join(", ")))
+        }
+    };
+    if app.config.total_return {
+        title.spans.push(Span::styled(
+            "  total return",
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    let unrealized: f64 = app
+        .filtered_indices()
+        .into_iter()
+        .filter(|&i| app.positions[i].open)
+        .map(|i| base[i].roi_value())
+        .sum();
+    if app.positions.iter().any(|p| p.open) {
+        title.spans.push(Span::raw("  unrealized "));
+        title.spans.push(Span::styled(
+            app.base_money(unrealized),
+            Style::default().fg(if unrealized >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+        ));
+    }
+    for issue in app
+        .config_error
+        .iter()
+        .chain(&fx_issue)
+        .chain(&app.instruments_error)
+        .chain(&app.quote_cache_error)
+        .chain(app.prices.errors.first())
+    {
+        title.spans.push(Span::styled(
+            format!("  ⚠ {issue}"),
+            Style::default().fg(Color::Yellow),
+        ));
+    }
+    if let Some(status) = &app.quote_status {
+        title.spans.push(Span::styled(
+            format!("  {status}"),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    // Account switcher: "All" and each account, the current one highlighted.
+    let mut switcher = vec![Span::raw("Portfolio Snapshot ")];
+    let views = std::iter::once(None).chain(accounts.iter().map(|a| Some(a.as_str())));
+    for view in views {
+        let label = view.unwrap_or("All");
+        let style = if view == app.account.as_deref() {
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else if view.is_some_and(|a| app.config.is_tax_advantaged(a)) {
+            Style::default().fg(Color::Blue)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        switcher.push(Span::styled(format!(" {label} "), style));
+    }
+    if !accounts.is_empty() {
+        switcher.push(Span::styled(" [/] ", Style::default().fg(Color::DarkGray)));
+    }
 
     let block = Paragraph::new(title).alignment(Alignment::Center).block(
         Block::default()
             .borders(Borders::ALL)
-            .title("Portfolio Snapshot"),
+            .title(Line::from(switcher)),
     );
     f.render_widget(block, area);
 }
@@ -968,13 +2477,40 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
 fn draw_footer(f: &mut Frame, area: Rect, mode: Mode) {
     let hint = match mode {
         Mode::Portfolio => {
-            "↑/↓ select  • enter/d detail  • f filter  • a add  • e edit  • x delete  • i import  • h help  • q quit"
+            "↑/↓ select  • enter/d detail  • [/] account  • f filter  • a add  • e edit  • x delete  • i import  • h help  • q quit"
         }
         Mode::Detail => {
-            "↑/↓ move  • f filter  • b/esc back  • e edit  • x delete  • a add  • i import  • q quit"
+            "↑/↓ move  • f filter  • b/esc back  • e edit  • n notes/tags  • x delete  • a add  • i import  • q quit"
+        }
+        Mode::AddForm => {
+            "tab/shift+tab field  • ←/→ cursor  • ctrl-w/ctrl-u delete  • enter next/save  • esc cancel"
+        }
+        Mode::Import => {
+            "type path  • tab complete  • ↑/↓ browse  • enter preview/open  • esc cancel"
+        }
+        Mode::ImportSheet => "↑/↓ choose sheet  • enter preview  • esc back",
+        Mode::ImportMapping => {
+            "↑/↓ field  • ←/→ change  • pgup/pgdn header row  • enter save profile  • esc back"
+        }
+        Mode::ImportPreview => {
+            "↑/↓ move  • space toggle row  • a toggle all  • d dividends  • p duplicates  • enter import selected  • esc back"
         }
-        Mode::AddForm => "tab/shift+tab move  • enter next/save  • esc cancel",
-        Mode::Import => "type path  • enter import  • esc cancel",
+        Mode::ConfirmDelete => "y/enter move to trash  • n/esc cancel",
+        Mode::BulkEdit => "tab action  • type value  • enter apply to marked  • esc cancel",
+        Mode::Journal => {
+            "tab switch tags/notes  • enter new line in notes  • ctrl-s save  • esc cancel"
+        }
+        Mode::Tags => "g/b/esc back  • q quit",
+        Mode::Risk => "r/b/esc back  • q quit",
+        Mode::Sizing => "tab/shift+tab field  • enter add a position of this size  • esc cancel",
+        Mode::Splits => {
+            "↑/↓ select  • x remove split  • import a ticker,date,ratio CSV with i  • esc back"
+        }
+        Mode::Dividends => {
+            "↑/↓ select  • e ex-date  • x remove payment  • import broker activity or OFX with i  • esc back"
+        }
+        Mode::ExDate => "type the ex-date (blank: use the pay date)  • enter save  • esc cancel",
+        Mode::Trash => "↑/↓ select  • r/enter restore  • x purge permanently  • esc back",
         Mode::Help => "enter/esc back  • q quit",
     };
     let footer = Paragraph::new(Line::from(hint))
@@ -990,7 +2526,12 @@ fn draw_portfolio(f: &mut Frame, area: Rect, app: &App) {
         .split(area);
 
     draw_positions_table(f, chunks[0], app);
-    draw_portfolio_chart(f, chunks[1], app);
+    let charts = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(chunks[1]);
+    draw_portfolio_chart(f, charts[0], app);
+    draw_equity_chart(f, charts[1], app);
 }
 
 fn draw_detail(f: &mut Frame, area: Rect, app: &App) {
@@ -1006,17 +2547,19 @@ fn draw_detail(f: &mut Frame, area: Rect, app: &App) {
 fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
     let filtered = app.filtered_positions();
     let header = Row::new(vec![
-        "Pos", "Ticker", "Cost", "Qty", "Sale", "PnL$", "ROI%", "Days", "Bought", "Sold",
+        "Pos", "Ticker", "Cost", "Qty", "Sale", "PnL$", "ROI%", "Alpha%", "MAE%", "MFE%", "Days",
+        "Bought", "Sold",
     ])
     .style(Style::default().fg(Color::Yellow));
 
     let mut rows: Vec<Row> = filtered
         .iter()
         .enumerate()
-        .map(|(display_idx, (_, p))| {
+        .map(|(display_idx, (idx, p))| {
+            let marked = app.marked.contains(idx);
             let pnl_val = p.roi_value();
             let pnl = Cell::from(Span::styled(
-                format_currency(pnl_val),
+                app.money(pnl_val, p),
                 Style::default().fg(if pnl_val >= 0.0 {
                     Color::Green
                 } else {
@@ -1024,27 +2567,77 @@ fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
                 }),
             ));
             let roi = Cell::from(styled_roi_pct(p.roi_pct()));
-            Row::new(vec![
-                Cell::from(format!("#{}", display_idx + 1)),
-                Cell::from(p.ticker.as_str()),
-                Cell::from(format_currency(p.cost_per_share)),
-                Cell::from(format!("{:.2}", p.quantity)),
-                Cell::from(format_currency(p.sale_price)),
+            let ex = app.excursion(p);
+            let excursion_cell = |pct: fn(&Excursion) -> f64| match &ex {
+                Some(ex) => Cell::from(styled_roi_pct(pct(ex))),
+                None => Cell::from("–"),
+            };
+            let row = Row::new(vec![
+                Cell::from(format!(
+                    "{}#{}",
+                    if marked { "*" } else { " " },
+                    display_idx + 1
+                )),
+                Cell::from(p.label()),
+                Cell::from(app.money(p.cost_per_share, p)),
+                Cell::from(app.quantity(p)),
+                Cell::from(app.money(p.sale_price, p)),
                 pnl,
                 roi,
+                match app.comparison(p) {
+                    Some(c) => Cell::from(styled_roi_pct(c.excess)),
+                    None => Cell::from("–"),
+                },
+                excursion_cell(|ex| ex.mae_pct),
+                excursion_cell(|ex| ex.mfe_pct),
                 Cell::from(p.days_held().to_string()),
                 Cell::from(p.purchase_date.format(DATE_FMT).to_string()),
-                Cell::from(p.sale_date.format(DATE_FMT).to_string()),
-            ])
+                Cell::from(if p.open {
+                    "open".to_string()
+                } else {
+                    p.sale_date.format(DATE_FMT).to_string()
+                }),
+            ]);
+            if marked {
+                row.style(Style::default().add_modifier(Modifier::BOLD))
+            } else {
+                row
+            }
         })
         .collect();
 
-    let summary =
-        summarize_positions(&filtered.iter().map(|(_, p)| *p).collect::<Vec<&Position>>());
+    // Summary rows add up positions in the base currency.
+    let base = app.base_positions();
+    let marked_only = app.summary_marked_only && !app.marked.is_empty();
+    let summarized: Vec<(usize, &Position)> = filtered
+        .iter()
+        .filter(|(i, _)| !marked_only || app.marked.contains(i))
+        .copied()
+        .collect();
+    let summary = summarize_positions(
+        &summarized
+            .iter()
+            .map(|(i, _)| &base[*i])
+            .collect::<Vec<&Position>>(),
+    );
+    // Excursions are ratios, so they are averaged in each position's currency.
+    let excursions: Vec<(&Position, Excursion)> = summarized
+        .iter()
+        .filter_map(|(_, p)| Some((*p, app.excursion(p)?)))
+        .collect();
+    let avg_excursion = |pct: fn(&Excursion) -> f64| {
+        if excursions.is_empty() {
+            Cell::from("")
+        } else {
+            let sum: f64 = excursions.iter().map(|(_, ex)| pct(ex)).sum();
+            Cell::from(styled_roi_pct(sum / excursions.len() as f64))
+        }
+    };
+    let efficiency = exit_efficiency(excursions.iter().copied());
 
     let mut summary_rows = Vec::new();
     let avg_pnl = Cell::from(Span::styled(
-        format_currency(summary.avg_pnl),
+        app.base_money(summary.avg_pnl),
         Style::default().fg(if summary.avg_pnl >= 0.0 {
             Color::Green
         } else {
@@ -1065,6 +2658,9 @@ fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
             Cell::from(""),
             avg_pnl,
             Cell::from(styled_roi_pct(summary.avg_roi_pct)),
+            Cell::from(""),
+            avg_excursion(|ex| ex.mae_pct),
+            avg_excursion(|ex| ex.mfe_pct),
             Cell::from(format!("{:.1}", summary.avg_days)),
             Cell::from(""),
             Cell::from(""),
@@ -1073,7 +2669,7 @@ fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
     );
 
     let total_pnl = Cell::from(Span::styled(
-        format_currency(summary.total_pnl),
+        app.base_money(summary.total_pnl),
         Style::default().fg(if summary.total_pnl >= 0.0 {
             Color::Green
         } else {
@@ -1094,6 +2690,13 @@ fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
             Cell::from(""),
             total_pnl,
             Cell::from(styled_roi_pct(summary.weighted_roi_pct)),
+            Cell::from(""),
+            Cell::from(if efficiency.is_some() { "exit" } else { "" }),
+            Cell::from(
+                efficiency
+                    .map(|e| format!("{:.0}%", e * 100.0))
+                    .unwrap_or_default(),
+            ),
             Cell::from(summary.total_days.to_string()),
             Cell::from(""),
             Cell::from(""),
@@ -1103,20 +2706,38 @@ fn draw_positions_table(f: &mut Frame, area: Rect, app: &App) {
 
     rows.extend(summary_rows);
 
+    // Option labels and crypto quantities can outgrow the usual widths.
+    let width = |text: &dyn Fn(&Position) -> String, min: u16| {
+        filtered
+            .iter()
+            .map(|(_, p)| text(p).len() as u16)
+            .max()
+            .unwrap_or(0)
+            .max(min)
+    };
     let widths = [
-        Constraint::Length(4),
+        Constraint::Length(5),
+        Constraint::Length(width(&|p| p.label(), 10)),
         Constraint::Length(10),
+        Constraint::Length(width(&|p| app.quantity(p), 8)),
         Constraint::Length(10),
         Constraint::Length(8),
         Constraint::Length(10),
         Constraint::Length(8),
-        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(8),
         Constraint::Length(6),
         Constraint::Length(12),
         Constraint::Length(12),
     ];
 
     let mut title = "Positions".to_string();
+    if !app.marked.is_empty() {
+        title.push_str(&format!(" – {} marked", app.marked.len()));
+        if marked_only {
+            title.push_str(" (summary: marked)");
+        }
+    }
     if app.filter_editing {
         title.push_str(" – filter: typing...");
     } else if !app.filter_text.is_empty() {
@@ -1191,6 +2812,88 @@ fn draw_portfolio_chart(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(chart, area);
 }
 
+/// Cumulative PnL against the same money held in the benchmark over the
+/// same days, in the base currency.
+fn draw_equity_chart(f: &mut Frame, area: Rect, app: &App) {
+    let base = app.base_positions();
+    let shown: Vec<&Position> = app.filtered_indices().iter().map(|&i| &base[i]).collect();
+    let benchmark = app.benchmark();
+    let curve = equity_curve(&shown, benchmark);
+
+    let mut datasets = vec![
+        Dataset::default()
+            .name("Portfolio")
+            .marker(symbols::Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(Style::default().fg(Color::Cyan))
+            .data(&curve.portfolio),
+    ];
+    let benchmark_name = format!("{} buy-and-hold", app.config.benchmark);
+    if benchmark.is_some() {
+        datasets.push(
+            Dataset::default()
+                .name(benchmark_name.as_str())
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(Color::Yellow))
+                .data(&curve.benchmark),
+        );
+    }
+
+    let title = match benchmark {
+        Some(_) if curve.compared > 0 => format!(
+            "Equity vs {} – beat it in {} of {} ({:.0}%)",
+            app.config.benchmark,
+            curve.beat,
+            curve.compared,
+            curve.beat as f64 / curve.compared as f64 * 100.0
+        ),
+        Some(_) => format!("Equity vs {}", app.config.benchmark),
+        None if app.config.benchmark.is_empty() => "Equity".to_string(),
+        None => format!(
+            "Equity (no {} in {})",
+            prices::file_name(&app.config.benchmark),
+            app.config.price_history_dir
+        ),
+    };
+    let all: Vec<(f64, f64)> = curve
+        .portfolio
+        .iter()
+        .chain(if benchmark.is_some() {
+            &curve.benchmark[..]
+        } else {
+            &[]
+        })
+        .copied()
+        .collect();
+    let y_bounds = bounds_from_points(&all, 0.0, 0.0);
+    let last_day = curve.portfolio.last().map_or(1.0, |p| p.0.max(1.0));
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .x_axis(
+            Axis::default()
+                .title("Days")
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, last_day])
+                .labels(vec![Span::raw("0"), Span::raw(format!("{last_day:.0}"))]),
+        )
+        .y_axis(
+            Axis::default()
+                .title(format!("PnL {}", app.fx.base))
+                .style(Style::default().fg(Color::Gray))
+                .bounds(y_bounds)
+                .labels(vec![
+                    Span::raw(format!("{:.0}", y_bounds[0])),
+                    Span::raw(format!("{:.0}", y_bounds[1])),
+                ]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Notes lines shown in the detail panel; the rest is read in the editor.
+const MAX_DETAIL_NOTE_LINES: usize = 6;
+
 fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
     let Some(pos) = app.selected_position() else {
         let block =
@@ -1199,15 +2902,10 @@ fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
         return;
     };
 
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(7), Constraint::Min(5)])
-        .split(area);
-
-    let info = vec![
+    let mut info = vec![
         Line::from(vec![
             Span::styled("Ticker ", Style::default().fg(Color::Gray)),
-            Span::styled(pos.ticker.as_str(), Style::default().fg(Color::Yellow)),
+            Span::styled(pos.label(), Style::default().fg(Color::Yellow)),
         ]),
         Line::from(vec![
             Span::styled("ROI ", Style::default().fg(Color::Gray)),
@@ -1221,9 +2919,12 @@ fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
             styled_roi_pct(pos.roi_per_day()),
         ]),
         Line::from(vec![
-            Span::styled("PnL ", Style::default().fg(Color::Gray)),
             Span::styled(
-                format_currency(pos.roi_value()),
+                if pos.open { "Unrealized PnL " } else { "PnL " },
+                Style::default().fg(Color::Gray),
+            ),
+            Span::styled(
+                app.money(pos.roi_value(), pos),
                 Style::default().fg(if pos.roi_value() >= 0.0 {
                     Color::Green
                 } else {
@@ -1232,31 +2933,210 @@ fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
             ),
         ]),
         Line::from(format!(
-            "Held {} days  {} -> {}",
+            "Held {} days  {} -> {}{}",
             pos.days_held(),
             pos.purchase_date.format(DATE_FMT),
-            pos.sale_date.format(DATE_FMT)
+            pos.sale_date.format(DATE_FMT),
+            match (pos.open, app.prices.get(&pos.symbol())) {
+                (false, _) => String::new(),
+                (true, Some(_)) => "  (open, marked at the latest close)".to_string(),
+                (true, None) => format!(
+                    "  (open, no {} in {})",
+                    prices::file_name(&pos.symbol()),
+                    app.config.price_history_dir
+                ),
+            }
         )),
         Line::from(format!(
-            "Invested {}  Proceeds {}  Qty {:.2}",
-            format_currency(pos.invested()),
-            format_currency(pos.proceeds()),
-            pos.quantity
+            "Invested {}  Proceeds {}  Qty {}",
+            app.money(pos.invested(), pos),
+            app.money(pos.proceeds(), pos),
+            app.quantity(pos)
         )),
     ];
+    if pos.stop_price.is_some() || pos.target_price.is_some() {
+        let mut line = Vec::new();
+        if let Some(stop) = pos.stop_price {
+            line.push(Span::styled("Stop ", Style::default().fg(Color::Gray)));
+            line.push(Span::raw(format!("{}  ", app.money(stop, pos))));
+        }
+        if let Some(target) = pos.target_price {
+            line.push(Span::styled("Target ", Style::default().fg(Color::Gray)));
+            line.push(Span::raw(format!("{}  ", app.money(target, pos))));
+        }
+        if let Some(r) = pos.r_multiple() {
+            line.push(Span::styled(
+                if pos.open { "Open R " } else { "R " },
+                Style::default().fg(Color::Gray),
+            ));
+            line.push(Span::styled(
+                format!("{r:+.2}  "),
+                Style::default().fg(if r >= 0.0 { Color::Green } else { Color::Red }),
+            ));
+        }
+        if let Some(ratio) = pos.reward_risk() {
+            line.push(Span::styled(
+                "Planned reward:risk ",
+                Style::default().fg(Color::Gray),
+            ));
+            line.push(Span::raw(format!("{ratio:.2}:1")));
+        }
+        info.push(Line::from(line));
+    }
+    if let Some(comparison) = app.comparison(pos) {
+        info.push(Line::from(vec![
+            Span::styled(
+                format!("{} ", app.config.benchmark),
+                Style::default().fg(Color::Gray),
+            ),
+            styled_roi_pct(comparison.benchmark),
+            Span::styled("  Excess ", Style::default().fg(Color::Gray)),
+            styled_roi_pct(comparison.excess),
+        ]));
+    }
+    if let Some(ex) = app.excursion(pos) {
+        let mut line = vec![
+            Span::styled("MAE ", Style::default().fg(Color::Gray)),
+            Span::styled(app.money(ex.mae, pos), Style::default().fg(Color::Red)),
+            Span::raw(" "),
+            styled_roi_pct(ex.mae_pct),
+            Span::styled("  MFE ", Style::default().fg(Color::Gray)),
+            Span::styled(app.money(ex.mfe, pos), Style::default().fg(Color::Green)),
+            Span::raw(" "),
+            styled_roi_pct(ex.mfe_pct),
+        ];
+        if let Some(efficiency) = exit_efficiency([(pos, ex)]) {
+            line.push(Span::styled(
+                "  Exit efficiency ",
+                Style::default().fg(Color::Gray),
+            ));
+            line.push(Span::raw(format!("{:.0}%", efficiency * 100.0)));
+        }
+        info.push(Line::from(line));
+    }
+    let mut extras = Vec::new();
+    if pos.fees != 0.0 {
+        extras.push(format!("Fees {}", app.money(pos.fees, pos)));
+    }
+    if let Some(contract) = &pos.option {
+        extras.push(format!(
+            "Option {} {} strike {} expires {}",
+            pos.ticker,
+            contract.right.label(),
+            app.money(contract.strike, pos),
+            contract.expiry.format(DATE_FMT)
+        ));
+    }
+    if pos.option.is_none() && !pos.instrument.is_equity() {
+        extras.push(format!("Instrument {}", pos.instrument.label()));
+    }
+    if pos.multiplier != 1.0 {
+        extras.push(format!("Multiplier {}", pos.multiplier));
+    }
+    if let Some(lot) = &pos.lot_id {
+        extras.push(format!("Lot {lot}"));
+    }
+    if let Some(entered) = &pos.entered {
+        extras.push(format!(
+            "Split-adjusted from {:.2} @ {} → {}",
+            entered.quantity,
+            app.money(entered.cost_per_share, pos),
+            app.money(entered.sale_price, pos)
+        ));
+    }
+    let income = attribute(&app.positions, &app.dividends)
+        .get(app.selected)
+        .copied()
+        .unwrap_or_default();
+    if income != 0.0 {
+        let counted = if app.config.total_return {
+            " (in return)"
+        } else {
+            ""
+        };
+        extras.push(format!("Dividends {}{counted}", app.money(income, pos)));
+    }
+    if let Some(account) = &pos.account {
+        if app.config.is_tax_advantaged(account) {
+            extras.push(format!("Account {account} (tax-advantaged)"));
+        } else {
+            extras.push(format!("Account {account}"));
+        }
+    }
+    if !pos.tags.is_empty() {
+        extras.push(format!("Tags {}", pos.tags.join(", ")));
+    }
+    if !extras.is_empty() {
+        info.push(Line::from(Span::styled(
+            extras.join("  "),
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    if let Some(fx) = app.fx.breakdown(pos) {
+        info.push(Line::from(vec![
+            Span::styled(
+                format!("In {} ", app.fx.base),
+                Style::default().fg(Color::Gray),
+            ),
+            Span::raw(format!(
+                "PnL {} = price {} + FX {}  ({} {:.4} → {:.4})",
+                app.base_money(fx.pnl),
+                app.base_money(fx.price_pnl),
+                app.base_money(fx.fx_pnl),
+                app.fx.currency_of(pos),
+                fx.buy_rate,
+                fx.sale_rate
+            )),
+        ]));
+    } else if !app.fx.currency_of(pos).eq_ignore_ascii_case(&app.fx.base) {
+        info.push(Line::from(Span::styled(
+            format!(
+                "No {} rate for these dates; add one to {}",
+                app.fx.currency_of(pos),
+                app.config.fx_rates_file
+            ),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if !pos.notes.is_empty() {
+        info.push(Line::from(Span::styled(
+            "Notes",
+            Style::default().fg(Color::Gray),
+        )));
+        let mut notes = pos.notes.lines();
+        info.extend(notes.by_ref().take(MAX_DETAIL_NOTE_LINES).map(Line::from));
+        if notes.next().is_some() {
+            info.push(Line::from(Span::styled(
+                "… (n to read all)",
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(info.len() as u16 + 2),
+            Constraint::Min(5),
+        ])
+        .split(area);
+
+    let info_block = Paragraph::new(info).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Position detail"),
+    );
+    f.render_widget(info_block, chunks[0]);
+
+    let duration = pos.days_held().max(1) as f64;
+    let history = app.prices.get(&pos.symbol());
+    let points = match history {
+        Some(history) => roi_path(pos, history),
+        None => vec![(0.0, 0.0), (duration, pos.roi_pct() * 100.0)],
+    };
+    let y_bounds = bounds_from_points(&points, -5.0, 5.0);
+    let x_bounds = [0.0, duration.max(1.0)];
 
-    let info_block = Paragraph::new(info).block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title("Position detail"),
-    );
-    f.render_widget(info_block, chunks[0]);
-
-    let duration = pos.days_held().max(1) as f64;
-    let points = vec![(0.0, 0.0), (duration, pos.roi_pct() * 100.0)];
-    let y_bounds = bounds_from_points(&points, -5.0, 5.0);
-    let x_bounds = [0.0, duration.max(1.0)];
-
     let dataset = Dataset::default()
         .name("ROI over hold")
         .graph_type(GraphType::Line)
@@ -1264,7 +3144,10 @@ fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
         .data(&points);
 
     let chart = Chart::new(vec![dataset])
-        .block(Block::default().borders(Borders::ALL).title("ROI timeline"))
+        .block(Block::default().borders(Borders::ALL).title(match history {
+            Some(_) => "ROI timeline (daily closes)",
+            None => "ROI timeline (no price history)",
+        }))
         .x_axis(
             Axis::default()
                 .title("Days held")
@@ -1280,6 +3163,342 @@ fn draw_position_detail(f: &mut Frame, area: Rect, app: &App) {
     f.render_widget(chart, chunks[1]);
 }
 
+fn draw_journal(f: &mut Frame, area: Rect, app: &App) {
+    let (Some(journal), Some(pos)) = (&app.journal, app.selected_position()) else {
+        return;
+    };
+    let popup = centered_rect(70, 60, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(popup);
+    let focus =
+        |focused: bool| Style::default().fg(if focused { Color::Magenta } else { Color::Gray });
+
+    let tags = Paragraph::new(Line::from(
+        journal
+            .tags
+            .spans(!journal.notes_focused, "e.g. breakout, earnings, mistake"),
+    ))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(focus(!journal.notes_focused))
+            .title(format!("{} tags (comma separated)", pos.ticker)),
+    );
+    f.render_widget(tags, chunks[0]);
+
+    let notes = Paragraph::new(journal.notes.lines(journal.notes_focused))
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(focus(journal.notes_focused))
+                .title("Notes (ctrl-s to save)"),
+        );
+    f.render_widget(notes, chunks[1]);
+}
+
+/// Performance per setup tag over the positions matching the filter.
+fn draw_tag_breakdown(f: &mut Frame, area: Rect, app: &App) {
+    let base = app.base_positions();
+    let positions: Vec<&Position> = app
+        .filtered_indices()
+        .into_iter()
+        .map(|i| &base[i])
+        .collect();
+    let header = Row::new(vec![
+        "Tag",
+        "Trades",
+        "Win%",
+        "Total PnL",
+        "Avg PnL",
+        "Avg ROI",
+        "Wtd ROI",
+        "Avg days",
+    ])
+    .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = tag_breakdown(&positions)
+        .into_iter()
+        .map(|stats| {
+            let s = &stats.summary;
+            Row::new(vec![
+                Cell::from(stats.tag),
+                Cell::from(stats.count.to_string()),
+                Cell::from(format!(
+                    "{:.0}%",
+                    stats.wins as f64 / stats.count as f64 * 100.0
+                )),
+                Cell::from(app.base_money(s.total_pnl)),
+                Cell::from(app.base_money(s.avg_pnl)),
+                Cell::from(styled_roi_pct(s.avg_roi_pct)),
+                Cell::from(styled_roi_pct(s.weighted_roi_pct)),
+                Cell::from(format!("{:.1}", s.avg_days)),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(16),
+        Constraint::Length(7),
+        Constraint::Length(6),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(9),
+        Constraint::Length(9),
+        Constraint::Length(9),
+    ];
+    let mut title = "Performance by tag".to_string();
+    if !app.filter_text.is_empty() {
+        title.push_str(&format!(" (filter: {})", app.filter_text));
+    }
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(table, area);
+}
+
+fn draw_risk(f: &mut Frame, area: Rect, app: &App) {
+    let risk = app.risk();
+    let taxes = app.taxable_gains();
+    let money = |value: f64| {
+        Span::styled(
+            app.base_money(value),
+            Style::default().fg(if value >= 0.0 {
+                Color::Green
+            } else {
+                Color::Red
+            }),
+        )
+    };
+    let ratio = |value: Option<f64>| value.map_or("–".to_string(), |v| format!("{v:.2}"));
+    let row = |label: &str, value: Span<'static>| {
+        Line::from(vec![
+            Span::styled(format!("{label:<22}"), Style::default().fg(Color::Gray)),
+            value,
+        ])
+    };
+    let mut lines = vec![
+        row("Trading days", Span::raw(risk.days.to_string())),
+        row("Annual return", styled_roi_pct(risk.annual_return)),
+        row(
+            "Volatility",
+            Span::raw(format!("{:.2}%", risk.volatility * 100.0)),
+        ),
+        row(
+            "Downside deviation",
+            Span::raw(format!("{:.2}%", risk.downside_deviation * 100.0)),
+        ),
+        row(
+            "Max drawdown",
+            Span::raw(format!("{:.2}%", risk.max_drawdown * 100.0)),
+        ),
+        row("Sharpe", Span::raw(ratio(risk.sharpe))),
+        row("Sortino", Span::raw(ratio(risk.sortino))),
+        row("Calmar", Span::raw(ratio(risk.calmar))),
+        Line::from(""),
+        row("Taxable short-term", money(taxes.short_term)),
+        row("Taxable long-term", money(taxes.long_term)),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "Daily returns are realized PnL over the capital in closed positions each weekday, \
+                 annualized over {} days; risk-free rate {:.2}% (risk_free_rate in roi_config.json).",
+                risk::TRADING_DAYS,
+                app.config.risk_free_rate * 100.0
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "Taxable gains are price gains net of fees on closed positions, long-term after a \
+                 year; {} closed in tax-advantaged accounts (tax_advantaged_accounts) are left out.",
+                taxes.sheltered
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    if risk.days < 2 {
+        lines.insert(
+            0,
+            Line::from(Span::styled(
+                "Not enough closed positions for risk figures",
+                Style::default().fg(Color::Yellow),
+            )),
+        );
+    }
+    let mut title = "Risk".to_string();
+    if !app.filter_text.is_empty() {
+        title.push_str(&format!(" (filter: {})", app.filter_text));
+    }
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    let panel = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(panel, columns[0]);
+    draw_r_multiples(f, columns[1], app);
+}
+
+fn draw_r_multiples(f: &mut Frame, area: Rect, app: &App) {
+    let stats = app.r_stats();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("R-multiples (closed trades with a stop)");
+    if stats.trades == 0 {
+        let empty = Paragraph::new("Set a stop in the add/edit form to track R-multiples")
+            .style(Style::default().fg(Color::DarkGray))
+            .block(block);
+        f.render_widget(empty, area);
+        return;
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(3)])
+        .split(inner);
+
+    let styled_r = |r: f64| {
+        Span::styled(
+            format!("{r:+.2}R"),
+            Style::default().fg(if r >= 0.0 { Color::Green } else { Color::Red }),
+        )
+    };
+    let label = |text: &str| Span::styled(format!("{text:<13}"), Style::default().fg(Color::Gray));
+    let lines = vec![
+        Line::from(vec![
+            label("Trades"),
+            Span::raw(format!(
+                "{}  ({:.0}% winners)",
+                stats.trades,
+                stats.win_rate * 100.0
+            )),
+        ]),
+        Line::from(vec![label("Expectancy"), styled_r(stats.expectancy)]),
+        Line::from(vec![
+            label("Avg win/loss"),
+            styled_r(stats.avg_win),
+            Span::raw(" / "),
+            styled_r(stats.avg_loss),
+        ]),
+        Line::from(vec![label("Total"), styled_r(stats.total)]),
+    ];
+    f.render_widget(Paragraph::new(lines), rows[0]);
+
+    let data: Vec<(&str, u64)> = R_BUCKETS.iter().copied().zip(stats.histogram).collect();
+    let bar_width = (rows[1].width / R_BUCKETS.len() as u16)
+        .saturating_sub(1)
+        .max(3);
+    let chart = BarChart::default()
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_style(Style::default().fg(Color::Cyan))
+        .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    f.render_widget(chart, rows[1]);
+}
+
+fn draw_sizing(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(60, 50, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let block = Block::default()
+        .title("Position size")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    f.render_widget(block, popup);
+
+    let form = &app.sizing;
+    let mut lines: Vec<Line> = form
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, placeholder, input))| {
+            let active = idx == form.active;
+            let style = if active {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+            let mut spans = vec![Span::styled(format!("{label:<13}"), style)];
+            spans.extend(input.spans(active, placeholder));
+            Line::from(spans)
+        })
+        .collect();
+    lines.push(Line::from(Span::styled(
+        format!(
+            "Account size is the starting balance ({}) plus realized PnL.",
+            app.base_money(app.config.starting_balance)
+        ),
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines.push(Line::from(""));
+
+    match app.size_position() {
+        Ok(sizing) => {
+            let account = parse_number(form.value(1)).unwrap_or_default();
+            lines.push(Line::from(vec![
+                Span::styled("Quantity     ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format_exact(sizing.quantity, 0),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Risk         ", Style::default().fg(Color::Gray)),
+                Span::raw(format!(
+                    "{} ({:.2}% of account)",
+                    app.base_money(sizing.risk),
+                    sizing.risk / account * 100.0
+                )),
+            ]));
+            lines.push(Line::from(vec![
+                Span::styled("Value        ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    format!(
+                        "{} ({:.1}% of account)",
+                        app.base_money(sizing.value),
+                        sizing.account_pct * 100.0
+                    ),
+                    Style::default().fg(if sizing.account_pct > 1.0 {
+                        Color::Yellow
+                    } else {
+                        Color::White
+                    }),
+                ),
+            ]));
+            if sizing.account_pct > 1.0 {
+                lines.push(Line::from(Span::styled(
+                    "More than the account: this size needs margin",
+                    Style::default().fg(Color::Yellow),
+                )));
+            }
+            lines.push(Line::from(Span::styled(
+                "Enter opens the add form with this size",
+                Style::default().fg(Color::Green),
+            )));
+        }
+        Err(err) => {
+            let shown = form.error.clone().unwrap_or(err);
+            lines.push(Line::from(Span::styled(
+                shown,
+                Style::default().fg(if form.error.is_some() {
+                    Color::Red
+                } else {
+                    Color::DarkGray
+                }),
+            )));
+        }
+    }
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
 fn draw_form(f: &mut Frame, area: Rect, app: &App) {
     let form_area = centered_rect(70, 70, area);
     let title = if app.editing.is_some() {
@@ -1312,19 +3531,30 @@ fn draw_form(f: &mut Frame, area: Rect, app: &App) {
             Span::styled(field.label, Style::default().fg(Color::Gray))
         };
 
-        let value = if field.value.is_empty() {
-            Span::styled(field.placeholder, Style::default().fg(Color::DarkGray))
-        } else {
-            Span::raw(field.value.as_str())
-        };
-
-        items.push(ListItem::new(Line::from(vec![
-            label,
-            Span::raw(": "),
-            value,
-        ])));
+        let mut spans = vec![label, Span::raw(": ")];
+        spans.extend(field.input.spans(active, field.placeholder));
+        if active {
+            match field.input.feedback() {
+                Some(Ok(note)) => spans.push(Span::styled(
+                    format!("  {note}"),
+                    Style::default().fg(Color::Green),
+                )),
+                Some(Err(note)) => spans.push(Span::styled(
+                    format!("  {note}"),
+                    Style::default().fg(Color::Yellow),
+                )),
+                None => {}
+            }
+        }
+        items.push(ListItem::new(Line::from(spans)));
     }
 
+    if let Some(plan) = app.form.plan_summary() {
+        items.push(ListItem::new(Span::styled(
+            plan,
+            Style::default().fg(Color::Magenta),
+        )));
+    }
     if let Some(err) = &app.form.error {
         items.push(ListItem::new(Span::styled(
             err,
@@ -1337,9 +3567,10 @@ fn draw_form(f: &mut Frame, area: Rect, app: &App) {
 }
 
 fn draw_import_form(f: &mut Frame, area: Rect, app: &App) {
-    let form_area = centered_rect(70, 40, area);
+    let form_area = centered_rect(70, 70, area);
+    f.render_widget(ratatui::widgets::Clear, form_area);
     let block = Block::default()
-        .title("Import from CSV (ticker,cost,qty,sale,purchase_date,sale_date)")
+        .title("Import from CSV, Excel/ODS, OFX/QFX or IBKR Flex XML (ticker,cost,qty,sale,purchase_date,sale_date)")
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Magenta));
     f.render_widget(block, form_area);
@@ -1349,12 +3580,26 @@ fn draw_import_form(f: &mut Frame, area: Rect, app: &App) {
         vertical: 1,
     });
 
+    let form = &app.import_form;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(3)])
+        .split(inner);
+
+    let mut path_line = vec![Span::styled("Path: ", Style::default().fg(Color::Gray))];
+    path_line.extend(form.path.spans(
+        form.cursor.is_none() && !form.account_focused,
+        "e.g. ~/Downloads/export.csv",
+    ));
+    let mut account_line = vec![Span::styled("Account: ", Style::default().fg(Color::Gray))];
+    account_line.extend(
+        form.account
+            .spans(form.account_focused, "none (shift+tab to set, tab cycles)"),
+    );
     let mut lines = vec![
-        Line::from(vec![
-            Span::styled("Path: ", Style::default().fg(Color::Gray)),
-            Span::raw(app.import_form.path.as_str()),
-        ]),
-        Line::from("Press Enter to import, Esc to cancel"),
+        Line::from(path_line),
+        Line::from(account_line),
+        Line::from("Tab completes, ↑/↓ browse, Enter previews or opens a directory, Esc cancels"),
     ];
 
     if let Some(msg) = &app.import_form.message {
@@ -1371,7 +3616,724 @@ fn draw_import_form(f: &mut Frame, area: Rect, app: &App) {
     }
 
     let para = Paragraph::new(lines).block(Block::default());
-    f.render_widget(para, inner);
+    f.render_widget(para, chunks[0]);
+
+    let title = if form.showing_recent() {
+        "Recent imports".to_string()
+    } else {
+        format!("Matches ({})", form.entries.len())
+    };
+    let items: Vec<ListItem> = if form.entries.is_empty() {
+        let empty = if form.showing_recent() {
+            "No recent imports; type a path"
+        } else {
+            "No matching files or directories"
+        };
+        vec![ListItem::new(Span::styled(
+            empty,
+            Style::default().fg(Color::Gray),
+        ))]
+    } else {
+        form.entries
+            .iter()
+            .map(|entry| {
+                if entry.is_dir {
+                    ListItem::new(Span::styled(
+                        format!("{}/", entry.name),
+                        Style::default().fg(Color::Cyan),
+                    ))
+                } else {
+                    ListItem::new(entry.name.as_str())
+                }
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::TOP).title(title))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    state.select(form.cursor);
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_sheet_picker(f: &mut Frame, area: Rect, app: &App) {
+    let Some(picker) = &app.sheet_picker else {
+        return;
+    };
+    let popup = centered_rect(50, 50, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let items: Vec<ListItem> = picker
+        .sheets
+        .iter()
+        .map(|name| ListItem::new(name.as_str()))
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Magenta))
+                .title(format!("Choose a sheet in {}", picker.path)),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = ListState::default();
+    state.select(Some(picker.cursor));
+    f.render_stateful_widget(list, popup, &mut state);
+}
+
+fn draw_mapping_wizard(f: &mut Frame, area: Rect, app: &App) {
+    let Some(wizard) = &app.mapping_wizard else {
+        return;
+    };
+    let popup = centered_rect(90, 85, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let outer = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta))
+        .title(format!(
+            "Map columns – {} (header on line {})",
+            wizard.path,
+            wizard.header_line()
+        ));
+    let inner = outer.inner(popup);
+    f.render_widget(outer, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+        .split(inner);
+
+    let headers = wizard.headers();
+    let row_style = |row: usize| {
+        if row == wizard.active {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(Color::Gray)
+        }
+    };
+
+    let mut items: Vec<ListItem> = FIELD_LABELS
+        .iter()
+        .enumerate()
+        .map(|(i, label)| {
+            let value = match wizard.assignments[i] {
+                Some(col) => Span::raw(format!(
+                    "col {} – {}",
+                    col + 1,
+                    headers.get(col).map(|s| s.as_str()).unwrap_or("")
+                )),
+                None => Span::styled("unassigned", Style::default().fg(Color::DarkGray)),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(*label, row_style(i)),
+                Span::raw(": "),
+                value,
+            ]))
+        })
+        .collect();
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("Date format", row_style(ROW_DATE_FORMAT)),
+        Span::raw(": "),
+        Span::raw(DATE_FORMATS[wizard.date_format].0),
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("Numbers", row_style(ROW_NUMBER_FORMAT)),
+        Span::raw(": "),
+        Span::raw(if wizard.decimal_comma {
+            "1.234,56"
+        } else {
+            "1,234.56"
+        }),
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("Profile name", row_style(ROW_NAME)),
+        Span::raw(": "),
+        Span::raw(wizard.name.as_str()),
+    ])));
+    items.push(ListItem::new(" "));
+    if let Some(err) = &wizard.error {
+        items.push(ListItem::new(Span::styled(
+            err.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    f.render_widget(
+        List::new(items).block(Block::default().borders(Borders::RIGHT)),
+        chunks[0],
+    );
+
+    let rows: Vec<Row> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| {
+            Row::new(vec![
+                Cell::from((i + 1).to_string()),
+                Cell::from(h.as_str()),
+                Cell::from(wizard.samples(i).join("  |  ")),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Length(18),
+            Constraint::Min(10),
+        ],
+    )
+    .header(
+        Row::new(vec!["Col", "Header", "Sample values"]).style(Style::default().fg(Color::Yellow)),
+    );
+    f.render_widget(table, chunks[1]);
+}
+
+fn draw_import_preview(f: &mut Frame, area: Rect, app: &App) {
+    let Some(preview) = &app.import_preview else {
+        return;
+    };
+    let report = &preview.report;
+    let popup = centered_rect(90, 85, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+
+    let mapping = match &report.mapping {
+        Some(m) => format!("Header on line {}: {}", m.line, m.describe()),
+        None => "No header detected".to_string(),
+    };
+    let mut summary_lines = vec![
+        Line::from(vec![
+            Span::styled("File ", Style::default().fg(Color::Gray)),
+            Span::raw(preview.path.as_str()),
+            Span::raw(format!(
+                "  • {} of {} rows selected  • {} skipped",
+                report.selected_count(),
+                report.rows.len(),
+                report.skipped.len()
+            )),
+        ]),
+        {
+            let (new, duplicate, changed) = report.status_counts();
+            Line::from(vec![
+                Span::raw(format!(
+                    "{new} new  • {duplicate} duplicate  • {changed} changed  • matches: "
+                )),
+                Span::styled(
+                    preview.policy.label(),
+                    Style::default()
+                        .fg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" (p to change)", Style::default().fg(Color::Gray)),
+            ])
+        },
+        Line::from(Span::styled(mapping, Style::default().fg(Color::Cyan))),
+    ];
+    if let Some(account) = &preview.account {
+        summary_lines.push(Line::from(vec![
+            Span::styled("Into account ", Style::default().fg(Color::Gray)),
+            Span::styled(account.as_str(), Style::default().fg(Color::Yellow)),
+        ]));
+    }
+    if let Some(statement) = report.transaction_summary() {
+        summary_lines.push(Line::from(Span::styled(
+            statement,
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    let dividends = report.dividends();
+    if !dividends.is_empty() {
+        let total: f64 = dividends.iter().map(|d| d.amount).sum();
+        summary_lines.push(Line::from(vec![
+            Span::raw(format!("{} dividends ({total:.2}): ", dividends.len())),
+            if preview.include_dividends {
+                Span::styled("add to the ledger", Style::default().fg(Color::Green))
+            } else {
+                Span::styled("leave out", Style::default().fg(Color::DarkGray))
+            },
+            Span::styled(" (d to change)", Style::default().fg(Color::Gray)),
+        ]));
+    }
+    let summary_height = summary_lines.len() as u16 + 2;
+    let income = report.income_notes();
+    let skipped_height = ((report.skipped.len() + income.len()).min(8) + 2) as u16;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(summary_height),
+            Constraint::Min(5),
+            Constraint::Length(skipped_height),
+        ])
+        .split(popup);
+
+    let summary = Paragraph::new(summary_lines)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Magenta))
+                .title("Import preview"),
+        );
+    f.render_widget(summary, chunks[0]);
+
+    let header = Row::new(vec![
+        "", "Line", "Status", "Ticker", "Cost", "Qty", "Sale", "PnL$", "Bought", "Sold",
+    ])
+    .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = report
+        .rows
+        .iter()
+        .map(|r| {
+            let p = &r.position;
+            let style = if r.selected {
+                Style::default()
+            } else {
+                Style::default().fg(Color::DarkGray)
+            };
+            Row::new(vec![
+                Cell::from(if r.selected { "[x]" } else { "[ ]" }),
+                Cell::from(r.line.to_string()),
+                Cell::from(Span::styled(
+                    r.status.label(),
+                    Style::default().fg(match r.status {
+                        MatchStatus::New => Color::Green,
+                        MatchStatus::Duplicate(_) => Color::Yellow,
+                        MatchStatus::Changed(_) => Color::Magenta,
+                    }),
+                )),
+                Cell::from(p.label()),
+                Cell::from(app.money(p.cost_per_share, p)),
+                Cell::from(app.quantity(p)),
+                Cell::from(app.money(p.sale_price, p)),
+                Cell::from(app.money(p.roi_value(), p)),
+                Cell::from(p.purchase_date.format(DATE_FMT).to_string()),
+                Cell::from(if p.open {
+                    "open".to_string()
+                } else {
+                    p.sale_date.format(DATE_FMT).to_string()
+                }),
+            ])
+            .style(style)
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(3),
+        Constraint::Length(6),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Rows to import"),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = TableState::default();
+    if !report.rows.is_empty() {
+        state.select(Some(preview.cursor));
+    }
+    f.render_stateful_widget(table, chunks[1], &mut state);
+
+    let mut skipped: Vec<ListItem> = report
+        .skipped
+        .iter()
+        .map(|s| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("Line {}: ", s.line),
+                    Style::default().fg(Color::Gray),
+                ),
+                Span::styled(s.reason.as_str(), Style::default().fg(Color::Red)),
+            ]))
+        })
+        .collect();
+    skipped.extend(income.into_iter().map(|(line, note)| {
+        ListItem::new(Line::from(vec![
+            Span::styled(format!("Line {line}: "), Style::default().fg(Color::Gray)),
+            Span::styled(note, Style::default().fg(Color::Cyan)),
+        ]))
+    }));
+    if skipped.is_empty() {
+        skipped.push(ListItem::new(Span::styled(
+            "No rows skipped",
+            Style::default().fg(Color::Gray),
+        )));
+    }
+    let skipped_list = List::new(skipped).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Skipped rows ({})", report.skipped.len())),
+    );
+    f.render_widget(skipped_list, chunks[2]);
+}
+
+fn draw_confirm_delete(f: &mut Frame, area: Rect, app: &App) {
+    let Some((targets, _)) = &app.pending_delete else {
+        return;
+    };
+    let positions: Vec<&Position> = targets
+        .iter()
+        .filter_map(|&i| app.positions.get(i))
+        .collect();
+    let popup = centered_rect(50, 30, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let lines = match positions.as_slice() {
+        [pos] => single_delete_lines(app, pos),
+        many => {
+            let mut tickers: Vec<&str> = many.iter().map(|p| p.ticker.as_str()).collect();
+            tickers.dedup();
+            let pnl: f64 = many.iter().map(|p| app.fx.to_base(p).roi_value()).sum();
+            vec![
+                Line::from(Span::styled(
+                    format!("{} positions", many.len()),
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD),
+                )),
+                Line::from(tickers.join(", ")),
+                Line::from(format!("Combined PnL {}", app.base_money(pnl))),
+                Line::from(" "),
+                Line::from(Span::styled(
+                    "Move all to trash? y/enter confirm, n/esc cancel",
+                    Style::default().fg(Color::Gray),
+                )),
+            ]
+        }
+    };
+    let dialog = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Delete"),
+        );
+    f.render_widget(dialog, popup);
+}
+
+fn single_delete_lines<'a>(app: &App, pos: &'a Position) -> Vec<Line<'a>> {
+    vec![
+        Line::from(vec![
+            Span::styled(
+                pos.label(),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "  {:.2} @ {} → {}",
+                pos.quantity,
+                app.money(pos.cost_per_share, pos),
+                app.money(pos.sale_price, pos)
+            )),
+        ]),
+        Line::from(format!(
+            "{} → {}",
+            pos.purchase_date.format(DATE_FMT),
+            pos.sale_date.format(DATE_FMT)
+        )),
+        Line::from(vec![
+            Span::raw(format!("PnL {}  ROI ", app.money(pos.roi_value(), pos))),
+            styled_roi_pct(pos.roi_pct()),
+        ]),
+        Line::from(" "),
+        Line::from(Span::styled(
+            "Move to trash? y/enter confirm, n/esc cancel (t restores from the portfolio)",
+            Style::default().fg(Color::Gray),
+        )),
+    ]
+}
+
+fn draw_bulk_edit(f: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(60, 30, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let bulk = &app.bulk;
+    let mut value = vec![Span::styled("Value: ", Style::default().fg(Color::Gray))];
+    value.extend(bulk.input.spans(true, ""));
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled("Action: ", Style::default().fg(Color::Gray)),
+            Span::styled(
+                bulk.action.label(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled("  (tab to change)", Style::default().fg(Color::Gray)),
+        ]),
+        Line::from(value),
+    ];
+    if let Some(Err(note)) = bulk.input.feedback() {
+        lines.push(Line::from(Span::styled(
+            note,
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if let Some(err) = &bulk.error {
+        lines.push(Line::from(Span::styled(
+            err.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let dialog = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title(format!("Bulk edit {} marked positions", app.marked.len())),
+    );
+    f.render_widget(dialog, popup);
+}
+
+fn draw_splits(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(area);
+    let header = Row::new(vec!["Ticker", "Date", "Ratio", "Positions"])
+        .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = app
+        .splits
+        .iter()
+        .map(|split| {
+            let affected = app
+                .positions
+                .iter()
+                .filter(|p| {
+                    p.entered.is_some()
+                        && p.ticker.eq_ignore_ascii_case(&split.ticker)
+                        && p.purchase_date < split.date
+                })
+                .count();
+            Row::new(vec![
+                Cell::from(split.ticker.clone()),
+                Cell::from(split.date.format(DATE_FMT).to_string()),
+                Cell::from(split.describe()),
+                Cell::from(affected.to_string()),
+            ])
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(10),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Splits ({})", app.splits.len())),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = TableState::default();
+    if !app.splits.is_empty() {
+        state.select(Some(app.splits_selected));
+    }
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    let note = app.splits_message.clone().unwrap_or_else(|| {
+        "Entered quantities and prices are restated for splits; the entered values are kept".into()
+    });
+    f.render_widget(
+        Paragraph::new(note).block(Block::default().borders(Borders::ALL)),
+        chunks[1],
+    );
+}
+
+fn draw_dividends(f: &mut Frame, area: Rect, app: &App) {
+    let header = Row::new(vec![
+        "Ticker",
+        "Ex-date",
+        "Pay date",
+        "Kind",
+        "Amount",
+        "Account",
+        "Positions",
+    ])
+    .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = app
+        .dividends
+        .iter()
+        .map(|dividend| {
+            let holders = app
+                .positions
+                .iter()
+                .filter(|p| dividend.entitles(p))
+                .count();
+            let ex_date = dividend
+                .ex_date
+                .map(|d| d.format(DATE_FMT).to_string())
+                .unwrap_or_else(|| "-".into());
+            let row = Row::new(vec![
+                Cell::from(dividend.ticker.clone()),
+                Cell::from(ex_date),
+                Cell::from(dividend.pay_date.format(DATE_FMT).to_string()),
+                Cell::from(dividend.kind.label()),
+                Cell::from(format!("{:.2}", dividend.amount)),
+                Cell::from(dividend.account.clone().unwrap_or_default()),
+                Cell::from(holders.to_string()),
+            ]);
+            if holders == 0 {
+                row.style(Style::default().fg(Color::DarkGray))
+            } else {
+                row
+            }
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(10),
+    ];
+    let total: f64 = app.dividends.iter().map(|d| d.amount).sum();
+    let orphans = unattributed(&app.positions, &app.dividends).len();
+    let mut title = format!("Dividends ({}, total {total:.2})", app.dividends.len());
+    if orphans > 0 {
+        title.push_str(&format!(
+            " — {orphans} with no position held over the ex-date"
+        ));
+    }
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = TableState::default();
+    if !app.dividends.is_empty() {
+        state.select(Some(app.dividends_selected));
+    }
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_ex_date(f: &mut Frame, area: Rect, app: &App) {
+    let Some(dividend) = app.dividends.get(app.dividends_selected) else {
+        return;
+    };
+    let popup = centered_rect(50, 20, area);
+    f.render_widget(ratatui::widgets::Clear, popup);
+    let mut value = vec![Span::styled("Ex-date: ", Style::default().fg(Color::Gray))];
+    value.extend(app.ex_date_input.spans(true, "YYYY-MM-DD"));
+    let mut lines = vec![Line::from(value)];
+    if let Some(Err(note)) = app.ex_date_input.feedback() {
+        lines.push(Line::from(Span::styled(
+            note,
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if let Some(err) = &app.ex_date_error {
+        lines.push(Line::from(Span::styled(
+            err.as_str(),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let dialog = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title(format!(
+                "{} {:.2} paid {}",
+                dividend.ticker,
+                dividend.amount,
+                dividend.pay_date.format(DATE_FMT)
+            )),
+    );
+    f.render_widget(dialog, popup);
+}
+
+fn draw_trash(f: &mut Frame, area: Rect, app: &App) {
+    let header = Row::new(vec!["Ticker", "Qty", "Bought", "Sold", "PnL$", "Deleted"])
+        .style(Style::default().fg(Color::Yellow));
+    let rows: Vec<Row> = app
+        .trash
+        .iter()
+        .map(|entry| {
+            let p = &entry.position;
+            Row::new(vec![
+                Cell::from(p.label()),
+                Cell::from(app.quantity(p)),
+                Cell::from(p.purchase_date.format(DATE_FMT).to_string()),
+                Cell::from(if p.open {
+                    "open".to_string()
+                } else {
+                    p.sale_date.format(DATE_FMT).to_string()
+                }),
+                Cell::from(app.money(p.roi_value(), p)),
+                Cell::from(
+                    entry
+                        .deleted_at
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string(),
+                ),
+            ])
+        })
+        .collect();
+    let retention = match app.config.trash_retention_days {
+        0 => "kept until purged".to_string(),
+        days => format!("purged after {days} days"),
+    };
+    let widths = [
+        Constraint::Length(10),
+        Constraint::Length(10),
+        Constraint::Length(12),
+        Constraint::Length(12),
+        Constraint::Length(14),
+        Constraint::Length(18),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Trash ({}, {retention})", app.trash.len())),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+    let mut state = TableState::default();
+    if !app.trash.is_empty() {
+        state.select(Some(app.trash_selected));
+    }
+    f.render_stateful_widget(table, area, &mut state);
 }
 
 fn draw_help(f: &mut Frame, area: Rect) {
@@ -1382,17 +4344,42 @@ fn draw_help(f: &mut Frame, area: Rect) {
         Line::from("  - ↑/↓ move selection"),
         Line::from("  - enter/d open position detail"),
         Line::from("  - f start ticker filter; type to refine, enter/esc to exit"),
-        Line::from("  - a add  • e edit  • x delete  • i import CSV"),
+        Line::from("  - a add  • e edit  • x delete (asks first)  • i import CSV"),
+        Line::from("  - t trash: r restores a deleted position, x purges it for good"),
+        Line::from("  - space marks a row, V marks a range, A marks all filtered, esc clears"),
+        Line::from(
+            "  - with rows marked: x deletes them, B bulk tag/fees/rename, m summary of marked",
+        ),
+        Line::from("  - g performance by tag; in the detail view n edits tags and notes"),
+        Line::from(
+            "  - r risk panel: volatility, drawdown, Sharpe, Sortino, Calmar and R-multiples",
+        ),
+        Line::from("  - s position sizing: shares for a risk % of the account, then add the trade"),
+        Line::from("  - [ / ] switch account (All, then each account)"),
+        Line::from("  - c splits: positions are split-adjusted; import a ticker,date,ratio CSV"),
+        Line::from("  - $ dividends ledger  • R total return: include dividends in ROI"),
+        Line::from("  - P reload price files and refetch quotes; open positions use the latest"),
         Line::from("  - h open this help, q quit"),
         Line::from(" "),
         Line::from("Form view:"),
-        Line::from("  - tab / shift+tab to move"),
+        Line::from("  - tab / shift+tab (or ↓/↑) to move between fields"),
+        Line::from("  - ←/→ home/end move the cursor; delete, ctrl-w word, ctrl-u to start"),
+        Line::from("  - numeric fields refuse letters; date fields show the parsed date"),
         Line::from("  - enter to advance or save on last field"),
         Line::from("  - esc to cancel"),
         Line::from(" "),
         Line::from("Import view:"),
-        Line::from("  - type CSV path, enter to import, esc to cancel"),
+        Line::from(
+            "  - type CSV, Excel/ODS, OFX/QFX or IBKR Flex XML path, enter to preview, esc to cancel",
+        ),
+        Line::from(
+            "  - tab completes the path (~ and $VAR expand); ↑/↓ browse matches or recent imports",
+        ),
         Line::from("  - columns: ticker,cost,qty,sale,purchase_date,sale_date"),
+        Line::from("  - preview: space toggles a row, a toggles all, enter imports selected"),
+        Line::from("  - p cycles what happens to duplicates: skip, replace, import anyway"),
+        Line::from("  - unrecognized headers open the column mapping wizard; the mapping"),
+        Line::from("    is saved as a profile and reused for files with the same header"),
     ];
 
     let block = Paragraph::new(text)
@@ -1401,18 +4388,33 @@ fn draw_help(f: &mut Frame, area: Rect) {
     f.render_widget(block, centered_rect(70, 70, area));
 }
 
-fn portfolio_stats(positions: &[Position]) -> (f64, f64, f64) {
-    let total_invested: f64 = positions.iter().map(|p| p.invested()).sum();
-    let total_proceeds: f64 = positions.iter().map(|p| p.proceeds()).sum();
+/// Totals for one account, or for all positions when `account` is `None`.
+fn portfolio_stats(positions: &[Position], account: Option<&str>) -> (f64, f64, f64) {
+    let in_account = |p: &&Position| account.is_none() || p.account.as_deref() == account;
+    let total_invested: f64 = positions
+        .iter()
+        .filter(in_account)
+        .map(|p| p.invested())
+        .sum();
+    let total_proceeds: f64 = positions
+        .iter()
+        .filter(in_account)
+        .map(|p| p.proceeds())
+        .sum();
+    let total_pnl: f64 = positions
+        .iter()
+        .filter(in_account)
+        .map(|p| p.roi_value())
+        .sum();
     let roi_pct = if total_invested.abs() < f64::EPSILON {
         0.0
     } else {
-        (total_proceeds - total_invested) / total_invested
+        total_pnl / total_invested
     };
     (total_invested, total_proceeds, roi_pct)
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize)]
 struct PositionSummary {
     total_pnl: f64,
     avg_pnl: f64,
@@ -1438,11 +4440,10 @@ fn summarize_positions(positions: &[&Position]) -> PositionSummary {
     let avg_days = total_days as f64 / count as f64;
 
     let total_invested = positions.iter().map(|p| p.invested()).sum::<f64>();
-    let total_proceeds = positions.iter().map(|p| p.proceeds()).sum::<f64>();
     let weighted_roi_pct = if total_invested.abs() < f64::EPSILON {
         0.0
     } else {
-        (total_proceeds - total_invested) / total_invested
+        total_pnl / total_invested
     };
 
     PositionSummary {
@@ -1455,10 +4456,6 @@ fn summarize_positions(positions: &[&Position]) -> PositionSummary {
     }
 }
 
-fn format_currency(value: f64) -> String {
-    format!("${:.2}", value)
-}
-
 fn styled_roi_pct(v: f64) -> Span<'static> {
     let color = if v > 0.0 {
         Color::Green
@@ -0,0 +1,316 @@
+use crate::import::{
+    ColumnMap, ExtraColumns, ImportProfile, NativeCells, ValueFormats, header_signature,
+    sanitize_header,
+};
+
+/// Date formats offered by the wizard: label and chrono format string.
+pub(crate) const DATE_FORMATS: [(&str, Option<&str>); 7] = [
+    ("auto (YYYY-MM-DD or MM/DD/YYYY)", None),
+    ("YYYY-MM-DD", Some("%Y-%m-%d")),
+    ("MM/DD/YYYY", Some("%m/%d/%Y")),
+    ("DD/MM/YYYY", Some("%d/%m/%Y")),
+    ("DD.MM.YYYY", Some("%d.%m.%Y")),
+    ("YYYYMMDD", Some("%Y%m%d")),
+    ("DD-Mon-YYYY", Some("%d-%b-%Y")),
+];
+
+/// Position fields that can be assigned a column, in wizard order.
+pub(crate) const FIELD_LABELS: [&str; 7] = [
+    "Ticker",
+    "Cost/share",
+    "Quantity",
+    "Sale price",
+    "Purchase date",
+    "Sale date",
+    "Lot id (optional)",
+];
+
+pub(crate) const ROW_DATE_FORMAT: usize = FIELD_LABELS.len();
+pub(crate) const ROW_NUMBER_FORMAT: usize = ROW_DATE_FORMAT + 1;
+pub(crate) const ROW_NAME: usize = ROW_NUMBER_FORMAT + 1;
+const ROW_COUNT: usize = ROW_NAME + 1;
+
+/// Lets the user map the columns of a file whose header wasn't recognized.
+pub(crate) struct MappingWizard {
+    pub(crate) path: String,
+    pub(crate) records: Vec<(usize, Vec<String>)>,
+    /// Typed spreadsheet cells, parsed without the wizard's formats.
+    pub(crate) native: NativeCells,
+    /// Index into `records` of the row treated as the header.
+    pub(crate) header_row: usize,
+    pub(crate) assignments: [Option<usize>; FIELD_LABELS.len()],
+    pub(crate) date_format: usize,
+    pub(crate) decimal_comma: bool,
+    pub(crate) name: String,
+    pub(crate) active: usize,
+    pub(crate) error: Option<String>,
+}
+
+impl MappingWizard {
+    pub(crate) fn new(path: &str, records: Vec<(usize, Vec<String>)>, native: NativeCells) -> Self {
+        let header_row = records
+            .iter()
+            .position(|(_, fields)| fields.iter().filter(|f| !f.trim().is_empty()).count() >= 3)
+            .unwrap_or(0);
+        let name = std::path::Path::new(path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "custom".into());
+        let mut wizard = Self {
+            path: path.to_string(),
+            records,
+            native,
+            header_row,
+            assignments: [None; FIELD_LABELS.len()],
+            date_format: 0,
+            decimal_comma: false,
+            name,
+            active: 0,
+            error: None,
+        };
+        wizard.guess_assignments();
+        wizard
+    }
+
+    pub(crate) fn headers(&self) -> &[String] {
+        self.records
+            .get(self.header_row)
+            .map(|(_, fields)| fields.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub(crate) fn header_line(&self) -> usize {
+        self.records
+            .get(self.header_row)
+            .map(|(line, _)| *line)
+            .unwrap_or(0)
+    }
+
+    /// The first few values below the header in column `col`.
+    pub(crate) fn samples(&self, col: usize) -> Vec<&str> {
+        self.records
+            .iter()
+            .skip(self.header_row + 1)
+            .filter_map(|(_, fields)| fields.get(col).map(|s| s.as_str()))
+            .filter(|s| !s.trim().is_empty())
+            .take(3)
+            .collect()
+    }
+
+    pub(crate) fn next_row(&mut self) {
+        self.active = (self.active + 1) % ROW_COUNT;
+    }
+
+    pub(crate) fn prev_row(&mut self) {
+        self.active = (self.active + ROW_COUNT - 1) % ROW_COUNT;
+    }
+
+    /// Cycles the value of the active row: column, date format or number style.
+    pub(crate) fn cycle(&mut self, forward: bool) {
+        match self.active {
+            ROW_DATE_FORMAT => {
+                let n = DATE_FORMATS.len();
+                self.date_format = if forward {
+                    (self.date_format + 1) % n
+                } else {
+                    (self.date_format + n - 1) % n
+                };
+            }
+            ROW_NUMBER_FORMAT => self.decimal_comma = !self.decimal_comma,
+            ROW_NAME => {}
+            field => {
+                // Options are "unassigned" followed by every column.
+                let n = self.headers().len() + 1;
+                let current = self.assignments[field].map(|c| c + 1).unwrap_or(0);
+                let next = if forward {
+                    (current + 1) % n
+                } else {
+                    (current + n - 1) % n
+                };
+                self.assignments[field] = next.checked_sub(1);
+            }
+        }
+    }
+
+    /// Moves the header row up or down and re-guesses the assignments.
+    pub(crate) fn move_header(&mut self, forward: bool) {
+        if forward && self.header_row + 1 < self.records.len() {
+            self.header_row += 1;
+        } else if !forward && self.header_row > 0 {
+            self.header_row -= 1;
+        } else {
+            return;
+        }
+        self.guess_assignments();
+    }
+
+    pub(crate) fn push_char(&mut self, c: char) {
+        if self.active == ROW_NAME {
+            self.name.push(c);
+        }
+    }
+
+    pub(crate) fn backspace(&mut self) {
+        if self.active == ROW_NAME {
+            self.name.pop();
+        }
+    }
+
+    pub(crate) fn build_profile(&self) -> Result<ImportProfile, String> {
+        let required = |field: usize| {
+            self.assignments[field]
+                .ok_or_else(|| format!("Assign a column to {}", FIELD_LABELS[field]))
+        };
+        let columns = ColumnMap {
+            ticker: required(0)?,
+            cost: required(1)?,
+            qty: required(2)?,
+            sale_price: required(3)?,
+            buy_date: required(4)?,
+            sale_date: required(5)?,
+            lot_id: self.assignments[6],
+            extras: ExtraColumns::default(),
+        };
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".into());
+        }
+        Ok(ImportProfile {
+            name: name.to_string(),
+            signature: header_signature(self.headers()),
+            columns,
+            formats: ValueFormats {
+                date_format: DATE_FORMATS[self.date_format].1.map(str::to_string),
+                decimal_comma: self.decimal_comma,
+            },
+        })
+    }
+
+    /// Pre-fills assignments from common header wording; the user adjusts the rest.
+    fn guess_assignments(&mut self) {
+        let mut guess = [None; FIELD_LABELS.len()];
+        for (i, raw) in self.headers().iter().enumerate() {
+            let h = sanitize_header(raw);
+            let slot = if h.contains("symbol") || h.contains("ticker") {
+                0
+            } else if h.contains("lot") {
+                6
+            } else if h.contains("date")
+                || h.contains("acquired")
+                || h.contains("sold")
+                || h.contains("opened")
+                || h.contains("closed")
+            {
+                if h.contains("sold")
+                    || h.contains("sale")
+                    || h.contains("sell")
+                    || h.contains("close")
+                {
+                    5
+                } else {
+                    4
+                }
+            } else if (h.contains("cost") || h.contains("basis") || h.contains("avg"))
+                && !h.contains("total")
+            {
+                1
+            } else if h.contains("shares")
+                || h.contains("qty")
+                || h.contains("quantity")
+                || h.contains("units")
+            {
+                2
+            } else if (h.contains("price") || h.contains("sale")) && !h.contains("total") {
+                3
+            } else {
+                continue;
+            };
+            if guess[slot].is_none() {
+                guess[slot] = Some(i);
+            }
+        }
+        self.assignments = guess;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::import::parse_position_records;
+
+    fn records(rows: &[&str]) -> Vec<(usize, Vec<String>)> {
+        rows.iter()
+            .enumerate()
+            .map(|(i, row)| (i + 1, row.split(';').map(str::to_string).collect()))
+            .collect()
+    }
+
+    fn journal() -> Vec<(usize, Vec<String>)> {
+        records(&[
+            "Exported 2026-03-01",
+            "Instrument;Units;Avg Cost;Exit Price;Opened;Closed;Lot Ref",
+            "KO;10;50,25;55,50;05.01.2026;05.02.2026;A1",
+            "PEP;4;1.170,00;1.182,00;06.01.2026;06.02.2026;A2",
+        ])
+    }
+
+    #[test]
+    fn guesses_columns_and_builds_a_profile() {
+        let mut wizard = MappingWizard::new("broker.csv", journal(), NativeCells::new());
+        assert_eq!(wizard.header_row, 1);
+        assert_eq!(
+            wizard.assignments,
+            [None, Some(2), Some(1), Some(3), Some(4), Some(5), Some(6)]
+        );
+        assert_eq!(
+            wizard.build_profile().unwrap_err(),
+            "Assign a column to Ticker"
+        );
+
+        wizard.active = 0;
+        wizard.cycle(true);
+        wizard.date_format = 4;
+        wizard.decimal_comma = true;
+        let profile = wizard.build_profile().unwrap();
+        assert_eq!(profile.name, "broker");
+        assert_eq!(
+            profile.signature,
+            "instrument,units,avgcost,exitprice,opened,closed,lotref"
+        );
+        assert_eq!(profile.columns.ticker, 0);
+        assert_eq!(profile.columns.lot_id, Some(6));
+        assert_eq!(profile.formats.date_format.as_deref(), Some("%d.%m.%Y"));
+
+        wizard.name = "  ".into();
+        assert!(wizard.build_profile().is_err());
+    }
+
+    #[test]
+    fn saved_profiles_match_by_header_signature() {
+        let mut wizard = MappingWizard::new("broker.csv", journal(), NativeCells::new());
+        wizard.assignments[0] = Some(0);
+        let mut config = Config::default();
+        // A first attempt with the wrong formats, then the corrected one.
+        config.upsert_profile(wizard.build_profile().unwrap());
+        wizard.date_format = 4;
+        wizard.decimal_comma = true;
+        config.upsert_profile(wizard.build_profile().unwrap());
+        assert_eq!(config.import_profiles.len(), 1);
+
+        let json = serde_json::to_string(&config).unwrap();
+        let config: Config = serde_json::from_str(&json).unwrap();
+        let report =
+            parse_position_records(&journal(), &NativeCells::new(), &config.import_profiles);
+        assert_eq!(report.mapping.unwrap().profile.as_deref(), Some("broker"));
+        assert_eq!(report.rows.len(), 2);
+        assert_eq!(report.rows[1].position.cost_per_share, 1170.0);
+        assert_eq!(report.rows[0].position.lot_id.as_deref(), Some("A1"));
+
+        // Same columns under other names is a different layout.
+        let mut renamed = journal();
+        renamed[1].1[0] = "Symbol".into();
+        let report = parse_position_records(&renamed, &NativeCells::new(), &config.import_profiles);
+        assert!(report.mapping.is_none());
+    }
+}
@@ -0,0 +1,472 @@
+use std::{collections::HashMap, fs};
+
+use chrono::NaiveDate;
+
+use crate::import::{
+    DetectedMapping, ImportReport, Side, SkippedRow, Trade, Transaction, ValueFormats,
+    match_lots_fifo,
+};
+use crate::options::parse_symbol;
+use crate::{parse_number, parse_ticker};
+
+/// An OFX aggregate or leaf element. OFX 1.x is SGML and leaves have no
+/// closing tag, so the tree is built without requiring one.
+#[derive(Debug, Default)]
+struct Node {
+    name: String,
+    line: usize,
+    text: String,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|c| c.name == name)
+    }
+
+    /// Follows a path of child names, e.g. `["INVTRAN", "FITID"]`.
+    fn find(&self, path: &[&str]) -> Option<&Node> {
+        path.iter().try_fold(self, |node, name| node.child(name))
+    }
+
+    fn text_at(&self, path: &[&str]) -> &str {
+        self.find(path).map(|n| n.text.as_str()).unwrap_or("")
+    }
+
+    fn number_at(&self, path: &[&str]) -> Option<f64> {
+        parse_number(self.text_at(path))
+    }
+
+    /// Every descendant named `name`, depth first.
+    fn descendants<'a>(&'a self, name: &str, out: &mut Vec<&'a Node>) {
+        for child in &self.children {
+            if child.name == name {
+                out.push(child);
+            } else {
+                child.descendants(name, out);
+            }
+        }
+    }
+}
+
+/// Reads an OFX/QFX investment statement. Buys and sells from the
+/// transaction list are matched first-in, first-out into closed lots; splits
+/// restate earlier trades and income is kept as statement transactions.
+pub(crate) fn parse_ofx_file(path: &str, today: NaiveDate) -> Result<ImportReport, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+    parse_ofx(&String::from_utf8_lossy(&bytes), today)
+}
+
+pub(crate) fn parse_ofx(data: &str, today: NaiveDate) -> Result<ImportReport, String> {
+    let root = parse_tree(data)?;
+    let statements: Vec<&Node> = {
+        let mut out = Vec::new();
+        root.descendants("INVSTMTRS", &mut out);
+        out
+    };
+    if statements.is_empty() {
+        return Err("No investment statement (INVSTMTRS) found in OFX file".into());
+    }
+
+    let securities = security_tickers(&root);
+    let mut report = ImportReport::default();
+    let mut trades = Vec::new();
+    let mut splits = Vec::new();
+    for statement in &statements {
+        let Some(list) = statement.child("INVTRANLIST") else {
+            continue;
+        };
+        for tx in &list.children {
+            let found = match read_transaction(tx, &securities) {
+                Ok(found) => found,
+                Err(reason) => {
+                    report.skipped.push(SkippedRow {
+                        line: tx.line,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            for entry in found {
+                match entry {
+                    Transaction::Trade(trade) => trades.push(trade),
+                    Transaction::Split {
+                        line,
+                        ticker,
+                        date,
+                        ratio,
+                    } => splits.push((ticker, date, ratio, line)),
+                    other => report.transactions.push(other),
+                }
+            }
+        }
+    }
+    if trades.is_empty() && splits.is_empty() && report.transactions.is_empty() {
+        return Err("OFX statement has no buy, sell, income or split transactions".into());
+    }
+
+    // Restate trades before each split in post-split shares so lots opened
+    // before it match sells made after it.
+    for (ticker, date, ratio, _) in &splits {
+        for trade in trades
+            .iter_mut()
+            .filter(|t| t.ticker == *ticker && t.option.is_none() && t.date < *date)
+        {
+            trade.quantity *= ratio;
+            trade.price /= ratio;
+        }
+    }
+    report
+        .transactions
+        .extend(trades.iter().cloned().map(Transaction::Trade));
+    report
+        .transactions
+        .extend(
+            splits
+                .into_iter()
+                .map(|(ticker, date, ratio, line)| Transaction::Split {
+                    line,
+                    ticker,
+                    date,
+                    ratio,
+                }),
+        );
+    match_lots_fifo(trades, today, &mut report);
+
+    report.mapping = Some(DetectedMapping {
+        line: statements[0].line,
+        headers: Vec::new(),
+        fields: Vec::new(),
+        formats: ValueFormats::default(),
+        profile: Some("OFX investment statement".into()),
+    });
+    Ok(report)
+}
+
+/// Maps each SECLIST unique id (usually a CUSIP) to its ticker.
+fn security_tickers(root: &Node) -> HashMap<String, String> {
+    let mut infos = Vec::new();
+    root.descendants("SECINFO", &mut infos);
+    infos
+        .into_iter()
+        .filter_map(|info| {
+            let id = info.text_at(&["SECID", "UNIQUEID"]).trim();
+            let ticker = info.text_at(&["TICKER"]).trim();
+            (!id.is_empty() && !ticker.is_empty())
+                .then(|| (id.to_string(), ticker.to_ascii_uppercase()))
+        })
+        .collect()
+}
+
+fn read_transaction(
+    tx: &Node,
+    securities: &HashMap<String, String>,
+) -> Result<Vec<Transaction>, String> {
+    let (side, detail) = match tx.name.as_str() {
+        "BUYSTOCK" | "BUYMF" | "BUYOTHER" | "BUYOPT" => (Side::Buy, "INVBUY"),
+        "SELLSTOCK" | "SELLMF" | "SELLOTHER" | "SELLOPT" => (Side::Sell, "INVSELL"),
+        "CLOSUREOPT" => return Ok(vec![read_closure(tx, securities)?]),
+        "INCOME" => return Ok(vec![read_income(tx, securities)?]),
+        // The income pays for new shares, which open a lot like a buy.
+        "REINVEST" => {
+            let buy = read_fill(tx, tx, Side::Buy, securities)?;
+            return Ok(vec![read_income(tx, securities)?, Transaction::Trade(buy)]);
+        }
+        "SPLIT" => return Ok(vec![read_split(tx, securities)?]),
+        _ => return Ok(Vec::new()),
+    };
+    let inv = tx
+        .child(detail)
+        .ok_or_else(|| format!("{} without {detail}", tx.name))?;
+    Ok(vec![Transaction::Trade(read_fill(
+        tx, inv, side, securities,
+    )?)])
+}
+
+/// A buy or sell from `inv`, which holds the INVTRAN, SECID, units, price
+/// and commissions.
+fn read_fill(
+    tx: &Node,
+    inv: &Node,
+    side: Side,
+    securities: &HashMap<String, String>,
+) -> Result<Trade, String> {
+    let quantity = inv
+        .number_at(&["UNITS"])
+        .map(f64::abs)
+        .filter(|q| *q > 0.0)
+        .ok_or("Invalid units")?;
+    let price = inv.number_at(&["UNITPRICE"]).ok_or("Invalid unit price")?;
+    let fees = ["COMMISSION", "FEES", "TAXES", "LOAD"]
+        .iter()
+        .filter_map(|name| inv.number_at(&[name]))
+        .map(f64::abs)
+        .sum();
+    let fitid = inv.text_at(&["INVTRAN", "FITID"]).trim();
+    let (ticker, option) = parse_symbol(&resolve_ticker(inv, securities)?)?;
+    Ok(Trade {
+        line: tx.line,
+        ticker,
+        option,
+        date: ofx_date(inv.text_at(&["INVTRAN", "DTTRADE"])).ok_or("Invalid trade date")?,
+        side,
+        quantity,
+        price,
+        fees,
+        id: (!fitid.is_empty()).then(|| format!("ofx:{fitid}")),
+    })
+}
+
+/// An option that expired, or was exercised or assigned: it closes at zero.
+fn read_closure(tx: &Node, securities: &HashMap<String, String>) -> Result<Transaction, String> {
+    let (ticker, option) = parse_symbol(&resolve_ticker(tx, securities)?)?;
+    let fitid = tx.text_at(&["INVTRAN", "FITID"]).trim();
+    Ok(Transaction::Trade(Trade {
+        line: tx.line,
+        ticker,
+        option,
+        date: ofx_date(tx.text_at(&["INVTRAN", "DTTRADE"])).ok_or("Invalid closure date")?,
+        side: Side::Sell,
+        quantity: tx
+            .number_at(&["UNITS"])
+            .map(f64::abs)
+            .filter(|q| *q > 0.0)
+            .ok_or("Invalid units")?,
+        price: 0.0,
+        fees: 0.0,
+        id: (!fitid.is_empty()).then(|| format!("ofx:{fitid}")),
+    }))
+}
+
+fn read_income(tx: &Node, securities: &HashMap<String, String>) -> Result<Transaction, String> {
+    Ok(Transaction::Income {
+        line: tx.line,
+        ticker: resolve_ticker(tx, securities)?,
+        date: ofx_date(tx.text_at(&["INVTRAN", "DTTRADE"])).ok_or("Invalid income date")?,
+        // OFX has no ex-date; the pay date stands in for it.
+        ex_date: None,
+        kind: tx.text_at(&["INCOMETYPE"]).trim().to_string(),
+        // A reinvestment's total is the (negative) cash spent on the shares.
+        amount: tx
+            .number_at(&["TOTAL"])
+            .map(|total| {
+                if tx.name == "REINVEST" {
+                    total.abs()
+                } else {
+                    total
+                }
+            })
+            .ok_or("Invalid income total")?,
+    })
+}
+
+fn read_split(tx: &Node, securities: &HashMap<String, String>) -> Result<Transaction, String> {
+    let ratio = match (tx.number_at(&["NUMERATOR"]), tx.number_at(&["DENOMINATOR"])) {
+        (Some(n), Some(d)) if n > 0.0 && d > 0.0 => n / d,
+        _ => match (tx.number_at(&["NEWUNITS"]), tx.number_at(&["OLDUNITS"])) {
+            (Some(new), Some(old)) if new > 0.0 && old > 0.0 => new / old,
+            _ => return Err("Split has no ratio".into()),
+        },
+    };
+    Ok(Transaction::Split {
+        line: tx.line,
+        ticker: resolve_ticker(tx, securities)?,
+        date: ofx_date(tx.text_at(&["INVTRAN", "DTTRADE"])).ok_or("Invalid split date")?,
+        ratio,
+    })
+}
+
+/// Looks the SECID up in the SECLIST; falls back to the raw id so the row
+/// still imports and the ticker can be fixed afterwards.
+fn resolve_ticker(node: &Node, securities: &HashMap<String, String>) -> Result<String, String> {
+    let id = node.text_at(&["SECID", "UNIQUEID"]).trim();
+    match securities.get(id) {
+        Some(ticker) => Ok(ticker.clone()),
+        None => parse_ticker(id).map_err(|_| "Transaction has no security id".to_string()),
+    }
+}
+
+/// OFX datetimes are `YYYYMMDD[HHMMSS[.XXX]][[gmt offset:tz]]`.
+fn ofx_date(raw: &str) -> Option<NaiveDate> {
+    let digits = raw.trim().get(..8)?;
+    NaiveDate::parse_from_str(digits, "%Y%m%d").ok()
+}
+
+/// Builds the element tree from the first `<OFX>` tag on, skipping the
+/// SGML header block or XML prolog. A leaf's text ends at the next tag
+/// whether or not it is closed, and stray closing tags are ignored.
+fn parse_tree(data: &str) -> Result<Node, String> {
+    let start = data
+        .find("<OFX>")
+        .ok_or("Not an OFX file: no <OFX> element")?;
+    let mut stack = vec![Node::default()];
+    let mut line = line_at(data, start);
+    let mut rest = &data[start..];
+
+    while let Some(open) = rest.find('<') {
+        let text = rest[..open].trim();
+        line += rest[..open].matches('\n').count();
+        if !text.is_empty() {
+            let top = stack.last_mut().expect("root stays on the stack");
+            top.text = decode_entities(text);
+        }
+        let close = rest[open..]
+            .find('>')
+            .ok_or_else(|| format!("Line {line}: unterminated tag"))?;
+        let tag = rest[open + 1..open + close].trim();
+        line += tag.matches('\n').count();
+        rest = &rest[open + close + 1..];
+
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            let name = name.trim().to_ascii_uppercase();
+            if let Some(depth) = stack.iter().rposition(|n| n.name == name)
+                && depth > 0
+            {
+                while stack.len() > depth {
+                    pop_into_parent(&mut stack);
+                }
+            }
+            continue;
+        }
+        // An open tag right after leaf text closes that leaf (SGML style).
+        if stack.len() > 1 && !stack.last().expect("non-empty").text.is_empty() {
+            pop_into_parent(&mut stack);
+        }
+        let name = tag.trim_end_matches('/').trim().to_ascii_uppercase();
+        let node = Node {
+            name,
+            line,
+            ..Node::default()
+        };
+        if tag.ends_with('/') {
+            stack.last_mut().expect("non-empty").children.push(node);
+        } else {
+            stack.push(node);
+        }
+    }
+    while stack.len() > 1 {
+        pop_into_parent(&mut stack);
+    }
+    Ok(stack.pop().expect("root"))
+}
+
+fn pop_into_parent(stack: &mut Vec<Node>) {
+    let node = stack.pop().expect("non-empty");
+    stack.last_mut().expect("root").children.push(node);
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+fn line_at(data: &str, offset: usize) -> usize {
+    data[..offset].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    fn fixture(name: &str) -> ImportReport {
+        let path = format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"));
+        let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        parse_ofx_file(&path, today).expect("fixture parses")
+    }
+
+    fn lots(report: &ImportReport, ticker: &str) -> Vec<Position> {
+        report
+            .rows
+            .iter()
+            .map(|r| r.position.clone())
+            .filter(|p| p.ticker == ticker)
+            .collect()
+    }
+
+    #[test]
+    fn sgml_statement_resolves_cusips_and_matches_fifo() {
+        let report = fixture("statement_v1.ofx");
+        let msft = lots(&report, "MSFT");
+        assert_eq!(msft.len(), 2);
+        assert_eq!(msft[0].quantity, 10.0);
+        assert!((msft[0].cost_per_share - 400.0).abs() < 1e-9);
+        assert!((msft[0].sale_price - 430.0).abs() < 1e-9);
+        // 4.95 buy commission (10 of 10 shares) + 15 of 5.00 sell commission.
+        assert!((msft[0].fees - (4.95 + 5.0 * 10.0 / 15.0)).abs() < 1e-9);
+        assert_eq!(msft[1].quantity, 5.0);
+        assert_eq!(msft[0].lot_id.as_deref(), Some("ofx:B-1001/ofx:S-2001"));
+    }
+
+    #[test]
+    fn splits_restate_earlier_lots() {
+        let report = fixture("statement_v1.ofx");
+        let nvda = lots(&report, "NVDA");
+        assert_eq!(nvda.len(), 1);
+        // 10 shares at 800 became 40 at 200 after the 4-for-1 split.
+        assert_eq!(nvda[0].quantity, 40.0);
+        assert!((nvda[0].cost_per_share - 200.0).abs() < 1e-9);
+        assert_eq!(
+            nvda[0].purchase_date,
+            NaiveDate::from_ymd_opt(2026, 1, 6).unwrap()
+        );
+    }
+
+    #[test]
+    fn income_and_open_lots_are_reported() {
+        let report = fixture("statement_v1.ofx");
+        let summary = report.transaction_summary().unwrap();
+        assert!(summary.contains("1 splits"), "{summary}");
+        assert!(summary.contains("12.50 income"), "{summary}");
+        let dividends = report.dividends();
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].amount, 12.5);
+        assert!(
+            report
+                .skipped
+                .iter()
+                .any(|s| s.reason.contains("MSFT still open"))
+        );
+    }
+
+    #[test]
+    fn xml_statement_parses_the_same_way() {
+        let report = fixture("statement_v2.ofx");
+        let msft = lots(&report, "MSFT");
+        assert_eq!(msft.len(), 1);
+        assert_eq!(msft[0].quantity, 10.0);
+        assert!((msft[0].sale_price - 430.0).abs() < 1e-9);
+        assert!((msft[0].fees - 9.95).abs() < 1e-9);
+        assert!(report.skipped.is_empty());
+    }
+
+    #[test]
+    fn reinvested_income_buys_shares() {
+        let report = fixture("statement_v2.ofx");
+        let ko = lots(&report, "KO");
+        assert_eq!(ko.len(), 1);
+        assert_eq!(ko[0].quantity, 2.0);
+        assert!((ko[0].cost_per_share - 60.0).abs() < 1e-9);
+        assert_eq!(ko[0].lot_id.as_deref(), Some("ofx:9003/ofx:9004"));
+        let dividends = report.dividends();
+        assert_eq!(dividends.len(), 1);
+        assert_eq!(dividends[0].amount, 120.0);
+        let summary = report.transaction_summary().unwrap();
+        assert!(summary.contains("2 buys, 2 sells"), "{summary}");
+        assert!(summary.contains("120.00 income"), "{summary}");
+    }
+
+    #[test]
+    fn rejects_files_without_a_statement() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        assert!(parse_ofx("hello", today).is_err());
+        assert!(parse_ofx("<OFX><SIGNONMSGSRSV1></SIGNONMSGSRSV1></OFX>", today).is_err());
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::{DATE_FMT, parse_ticker};
+
+/// Shares per standard equity option contract.
+pub(crate) const CONTRACT_SIZE: f64 = 100.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum OptionRight {
+    Call,
+    Put,
+}
+
+impl OptionRight {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            OptionRight::Call => "call",
+            OptionRight::Put => "put",
+        }
+    }
+
+    fn code(self) -> char {
+        match self {
+            OptionRight::Call => 'C',
+            OptionRight::Put => 'P',
+        }
+    }
+}
+
+/// The contract terms of an option position; the position's ticker is the
+/// underlying.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct OptionContract {
+    pub(crate) expiry: NaiveDate,
+    pub(crate) strike: f64,
+    pub(crate) right: OptionRight,
+}
+
+impl OptionContract {
+    /// OCC symbol: the root padded to six characters, then YYMMDD, C or P
+    /// and the strike in thousandths, e.g. `AAPL  260220C00150000`.
+    pub(crate) fn occ_symbol(&self, underlying: &str) -> String {
+        format!(
+            "{underlying:<6}{}{}{:08}",
+            self.expiry.format("%y%m%d"),
+            self.right.code(),
+            (self.strike * 1000.0).round() as u64
+        )
+    }
+
+    /// Short form for tables, e.g. `AAPL 150C 2026-02-20`.
+    pub(crate) fn label(&self, underlying: &str) -> String {
+        format!(
+            "{underlying} {}{} {}",
+            self.strike,
+            self.right.code(),
+            self.expiry.format(DATE_FMT)
+        )
+    }
+}
+
+/// Reads a ticker or an OCC option symbol, with or without the padding
+/// spaces. Returns the underlying and, for options, the contract.
+pub(crate) fn parse_symbol(raw: &str) -> Result<(String, Option<OptionContract>), String> {
+    match parse_occ(raw) {
+        Some((underlying, contract)) => Ok((underlying, Some(contract))),
+        None => Ok((parse_ticker(raw)?, None)),
+    }
+}
+
+fn parse_occ(raw: &str) -> Option<(String, OptionContract)> {
+    let symbol: String = raw
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_ascii_uppercase();
+    if !symbol.is_ascii() {
+        return None;
+    }
+    let split = symbol.len().checked_sub(15)?;
+    let (root, terms) = symbol.split_at(split);
+    if root.is_empty() || root.len() > 6 || !root.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let (date, rest) = terms.split_at(6);
+    let (right, strike) = rest.split_at(1);
+    if !strike.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let right = match right {
+        "C" => OptionRight::Call,
+        "P" => OptionRight::Put,
+        _ => return None,
+    };
+    let expiry = NaiveDate::parse_from_str(&format!("20{date}"), "%Y%m%d").ok()?;
+    let strike = strike.parse::<u64>().ok()? as f64 / 1000.0;
+    Some((
+        root.to_string(),
+        OptionContract {
+            expiry,
+            strike,
+            right,
+        },
+    ))
+}
+
+/// Broker activity codes for an option leaving the account without a trade:
+/// it expired, or was exercised or assigned. The contract closes at zero.
+pub(crate) fn is_closure_code(code: &str) -> bool {
+    matches!(
+        code.trim().to_ascii_uppercase().as_str(),
+        "OEXP" | "OASGN" | "OEXCS" | "EXPIRED" | "EXPIRE" | "ASSIGNED" | "EXERCISED"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn occ_symbols_round_trip() {
+        let (underlying, contract) = parse_symbol("aapl  260220C00150000").unwrap();
+        let contract = contract.unwrap();
+        assert_eq!(underlying, "AAPL");
+        assert_eq!(contract.right, OptionRight::Call);
+        assert_eq!(contract.strike, 150.0);
+        assert_eq!(
+            contract.expiry,
+            NaiveDate::from_ymd_opt(2026, 2, 20).unwrap()
+        );
+        assert_eq!(contract.occ_symbol(&underlying), "AAPL  260220C00150000");
+        assert_eq!(contract.label(&underlying), "AAPL 150C 2026-02-20");
+
+        let (underlying, contract) = parse_symbol("SPY261218P00452500").unwrap();
+        assert_eq!(underlying, "SPY");
+        assert_eq!(contract.unwrap().strike, 452.5);
+
+        assert_eq!(parse_symbol("msft").unwrap(), ("MSFT".to_string(), None));
+        assert_eq!(parse_symbol("AAPL  261320C00150000").unwrap().1, None);
+    }
+}
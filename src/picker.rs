@@ -0,0 +1,190 @@
+use std::{env, fs};
+
+/// File types the import dialog can read, shown in the file list.
+pub(crate) const IMPORT_EXTENSIONS: [&str; 8] =
+    ["csv", "xml", "ofx", "qfx", "xlsx", "xlsm", "xls", "ods"];
+
+/// A directory or importable file offered by the import dialog.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct PathEntry {
+    pub(crate) name: String,
+    /// The input that selects this entry, in the form the user typed it.
+    pub(crate) path: String,
+    pub(crate) is_dir: bool,
+}
+
+/// Expands a leading `~` and any `$VAR` or `${VAR}`. Unset variables are
+/// left as typed so the error message shows what was entered.
+pub(crate) fn expand_path(raw: &str) -> String {
+    let raw = raw.trim();
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    if (rest == "~" || rest.starts_with("~/"))
+        && let Some(home) = env::var_os("HOME")
+    {
+        out.push_str(&home.to_string_lossy());
+        rest = &rest[1..];
+    }
+
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        let (name, consumed) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        match env::var(name) {
+            Ok(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[idx..idx + 1 + consumed]),
+        }
+        rest = &after[consumed..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Splits input into the directory part, as typed, and the partial name after it.
+fn split_input(input: &str) -> (&str, &str) {
+    match input.rfind('/') {
+        Some(idx) => input.split_at(idx + 1),
+        None => ("", input),
+    }
+}
+
+/// Subdirectories and importable files in the directory of `input` whose
+/// names start with its last component, directories first. Hidden entries
+/// show up only once a `.` is typed.
+pub(crate) fn matching_entries(input: &str) -> Vec<PathEntry> {
+    let (dir_typed, prefix) = split_input(input);
+    let dir = match expand_path(dir_typed) {
+        d if d.is_empty() => ".".to_string(),
+        d => d,
+    };
+    let Ok(read) = fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    let prefix_lower = prefix.to_lowercase();
+    let mut entries: Vec<PathEntry> = read
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') && !prefix.starts_with('.') {
+                return None;
+            }
+            if !name.to_lowercase().starts_with(&prefix_lower) {
+                return None;
+            }
+            // Follow symlinks so linked directories can be browsed.
+            let is_dir = fs::metadata(entry.path()).map(|m| m.is_dir()).ok()?;
+            let importable = name
+                .rsplit_once('.')
+                .is_some_and(|(_, ext)| IMPORT_EXTENSIONS.contains(&ext.to_lowercase().as_str()));
+            if !is_dir && !importable {
+                return None;
+            }
+            let path = format!("{dir_typed}{name}{}", if is_dir { "/" } else { "" });
+            Some(PathEntry { name, path, is_dir })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+    entries
+}
+
+/// Tab completion: a single match is completed in full, several are
+/// extended to their longest common prefix. `None` when nothing matches.
+pub(crate) fn complete(input: &str) -> Option<String> {
+    let entries = matching_entries(input);
+    match entries.as_slice() {
+        [] => None,
+        [only] => Some(only.path.clone()),
+        [first, rest @ ..] => {
+            let common = rest.iter().fold(first.name.as_str(), |common, entry| {
+                let len = common
+                    .char_indices()
+                    .zip(entry.name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map(|((i, a), _)| i + a.len_utf8())
+                    .unwrap_or(0);
+                &common[..len]
+            });
+            let (dir_typed, prefix) = split_input(input);
+            if common.len() > prefix.len() {
+                Some(format!("{dir_typed}{common}"))
+            } else {
+                Some(input.to_string())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> String {
+        let dir = env::temp_dir().join(format!("roi-picker-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("statements")).unwrap();
+        for file in [
+            "trades_2025.csv",
+            "trades_2026.csv",
+            "notes.txt",
+            ".hidden.csv",
+        ] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        format!("{}/", dir.display())
+    }
+
+    #[test]
+    fn expands_home_and_variables() {
+        let home = env::var("HOME").unwrap_or_default();
+        assert_eq!(expand_path("~/data.csv"), format!("{home}/data.csv"));
+        assert_eq!(expand_path("${HOME}/x"), format!("{home}/x"));
+        assert_eq!(expand_path("$HOME/x"), format!("{home}/x"));
+        assert_eq!(
+            expand_path("$ROI_SURELY_UNSET_VAR/x"),
+            "$ROI_SURELY_UNSET_VAR/x"
+        );
+        assert_eq!(expand_path("a~b"), "a~b");
+    }
+
+    #[test]
+    fn lists_directories_and_importable_files() {
+        let dir = scratch_dir("list");
+        let names: Vec<String> = matching_entries(&dir).into_iter().map(|e| e.name).collect();
+        assert_eq!(names, ["statements", "trades_2025.csv", "trades_2026.csv"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn completes_to_common_prefix_then_unique_match() {
+        let dir = scratch_dir("complete");
+        assert_eq!(
+            complete(&format!("{dir}tr")),
+            Some(format!("{dir}trades_202"))
+        );
+        assert_eq!(
+            complete(&format!("{dir}trades_2026")),
+            Some(format!("{dir}trades_2026.csv"))
+        );
+        assert_eq!(
+            complete(&format!("{dir}st")),
+            Some(format!("{dir}statements/"))
+        );
+        assert_eq!(complete(&format!("{dir}zzz")), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
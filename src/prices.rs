@@ -0,0 +1,397 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+use chrono::NaiveDate;
+
+use crate::quotes::Quote;
+use crate::{Position, parse_date_any, parse_number};
+
+/// One day's prices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Bar {
+    pub(crate) open: f64,
+    pub(crate) high: f64,
+    pub(crate) low: f64,
+    pub(crate) close: f64,
+}
+
+/// Daily bars for one symbol, oldest first.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PriceHistory {
+    bars: BTreeMap<NaiveDate, Bar>,
+    /// Days whose bar was built from quotes rather than read from the file.
+    quoted: BTreeSet<NaiveDate>,
+}
+
+impl PriceHistory {
+    /// Reads a CSV with `Date` and `Close` columns; `Open`, `High` and `Low`
+    /// fall back to the close when missing. Header names are matched without
+    /// regard to case, so Yahoo Finance and most broker downloads work as-is.
+    pub(crate) fn parse(data: &str) -> Result<Self, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(data.as_bytes());
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| format!("unreadable header: {e}"))?
+            .iter()
+            .map(|h| h.to_ascii_lowercase())
+            .collect();
+        let col = |name: &str| headers.iter().position(|h| h == name);
+        let date = col("date").ok_or("no Date column")?;
+        let close = col("close").ok_or("no Close column")?;
+        let (open, high, low) = (col("open"), col("high"), col("low"));
+
+        let mut bars = BTreeMap::new();
+        for (i, record) in reader.records().enumerate() {
+            let line = i + 2;
+            let record = record.map_err(|e| format!("line {line}: {e}"))?;
+            let field = |n: Option<usize>| n.and_then(|n| record.get(n)).unwrap_or("");
+            let day = parse_date_any(field(Some(date)))
+                .map_err(|_| format!("line {line}: invalid date"))?;
+            // Vendors write "null" for holidays in some exports; skip those rows.
+            let Some(close) = parse_number(field(Some(close))) else {
+                continue;
+            };
+            let or_close = |n| parse_number(field(n)).unwrap_or(close);
+            bars.insert(
+                day,
+                Bar {
+                    open: or_close(open),
+                    high: or_close(high),
+                    low: or_close(low),
+                    close,
+                },
+            );
+        }
+        Ok(Self {
+            bars,
+            quoted: BTreeSet::new(),
+        })
+    }
+
+    /// Folds a quote into the day's bar, creating it if the file has none.
+    /// A day the file has a bar for keeps the file's prices, so a stale
+    /// quote cannot replace a newer close.
+    fn record(&mut self, quote: Quote) {
+        if self.bars.contains_key(&quote.date) && !self.quoted.contains(&quote.date) {
+            return;
+        }
+        self.quoted.insert(quote.date);
+        let price = quote.price;
+        self.bars
+            .entry(quote.date)
+            .and_modify(|bar| {
+                bar.close = price;
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+            })
+            .or_insert(Bar {
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+            });
+    }
+
+    /// The last bar on or before `date`.
+    pub(crate) fn latest(&self, date: NaiveDate) -> Option<(NaiveDate, &Bar)> {
+        self.bars.range(..=date).next_back().map(|(d, b)| (*d, b))
+    }
+
+    /// Bars from `from` through `to`, inclusive.
+    pub(crate) fn between(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> impl Iterator<Item = (NaiveDate, &Bar)> {
+        self.bars.range(from..=to.max(from)).map(|(d, b)| (*d, b))
+    }
+}
+
+/// Price histories read from a directory holding one CSV per symbol.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PriceStore {
+    histories: HashMap<String, PriceHistory>,
+    /// Files that exist but could not be read.
+    pub(crate) errors: Vec<String>,
+}
+
+impl PriceStore {
+    /// Reads the history of each symbol that has a file; symbols without one
+    /// are simply absent.
+    pub(crate) fn load<'a>(dir: &str, symbols: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut store = Self::default();
+        for symbol in symbols {
+            if store.histories.contains_key(symbol) {
+                continue;
+            }
+            let path = Path::new(dir).join(file_name(symbol));
+            if !path.exists() {
+                continue;
+            }
+            let parsed = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| PriceHistory::parse(&data));
+            match parsed {
+                Ok(history) => {
+                    store.histories.insert(symbol.to_string(), history);
+                }
+                Err(err) => store.errors.push(format!("{}: {err}", path.display())),
+            }
+        }
+        store
+    }
+
+    /// Adds a fetched quote on top of the symbol's history.
+    pub(crate) fn add_quote(&mut self, symbol: &str, quote: Quote) {
+        self.histories
+            .entry(symbol.to_string())
+            .or_default()
+            .record(quote);
+    }
+
+    pub(crate) fn get(&self, symbol: &str) -> Option<&PriceHistory> {
+        self.histories.get(symbol)
+    }
+}
+
+/// `AAPL.csv`; OCC option symbols lose their padding and pairs such as
+/// `BTC/USD` use a dash.
+pub(crate) fn file_name(symbol: &str) -> String {
+    let name: String = symbol
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| if c == '/' { '-' } else { c })
+        .collect();
+    format!("{name}.csv")
+}
+
+/// Values open positions at the latest close on or before `today`. Without
+/// a history the mark is left as it is and only the date moves to today.
+/// Options past their expiry are closed at zero.
+pub(crate) fn mark_to_market(positions: &mut [Position], prices: &PriceStore, today: NaiveDate) {
+    for pos in positions.iter_mut().filter(|p| p.open) {
+        // An option still open after its expiry expired worthless.
+        if let Some(contract) = pos.option.filter(|c| c.expiry < today) {
+            pos.open = false;
+            pos.sale_price = 0.0;
+            pos.sale_date = contract.expiry;
+            continue;
+        }
+        match prices.get(&pos.symbol()).and_then(|h| h.latest(today)) {
+            Some((date, bar)) if date >= pos.purchase_date => {
+                pos.sale_price = bar.close;
+                pos.sale_date = date;
+            }
+            _ => pos.sale_date = today.max(pos.purchase_date),
+        }
+    }
+}
+
+/// The position's ROI % at each close while it was held, starting at 0 on
+/// the purchase date and ending at its sale (or current mark).
+pub(crate) fn roi_path(pos: &Position, history: &PriceHistory) -> Vec<(f64, f64)> {
+    let roi_at = |price: f64| {
+        Position {
+            sale_price: price,
+            ..pos.clone()
+        }
+        .roi_pct()
+            * 100.0
+    };
+    let mut points = vec![(0.0, 0.0)];
+    points.extend(
+        history
+            .between(pos.purchase_date, pos.sale_date)
+            .filter(|(date, _)| *date < pos.sale_date)
+            .map(|(date, bar)| {
+                let day = (date - pos.purchase_date).num_days() as f64;
+                (day, roi_at(bar.close))
+            }),
+    );
+    points.push((pos.days_held() as f64, pos.roi_pct() * 100.0));
+    points
+}
+
+/// How far a trade went against the position (`mae`, at most zero) and in
+/// its favor (`mfe`, at least zero) while it was held, in the position's
+/// currency and as a fraction of the cost.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Excursion {
+    pub(crate) mae: f64,
+    pub(crate) mfe: f64,
+    pub(crate) mae_pct: f64,
+    pub(crate) mfe_pct: f64,
+}
+
+/// Uses the lows and highs from the purchase date through the sale date;
+/// `None` when the history has no bars in that range.
+pub(crate) fn excursion(pos: &Position, history: &PriceHistory) -> Option<Excursion> {
+    let bars: Vec<&Bar> = history
+        .between(pos.purchase_date, pos.sale_date)
+        .map(|(_, bar)| bar)
+        .collect();
+    if bars.is_empty() {
+        return None;
+    }
+    let cost = pos.cost_per_share;
+    let low = bars
+        .iter()
+        .map(|b| b.low)
+        .fold(cost.min(pos.sale_price), f64::min);
+    let high = bars
+        .iter()
+        .map(|b| b.high)
+        .fold(cost.max(pos.sale_price), f64::max);
+    let size = pos.quantity * pos.multiplier;
+    let pct = |price: f64| {
+        if cost > 0.0 {
+            (price - cost) / cost
+        } else {
+            0.0
+        }
+    };
+    Some(Excursion {
+        mae: (low - cost) * size,
+        mfe: (high - cost) * size,
+        mae_pct: pct(low),
+        mfe_pct: pct(high),
+    })
+}
+
+/// Captured price move over MFE, averaged over the closed trades that had
+/// any favorable excursion. Fees and dividends are left out, as MFE is a
+/// gross price move.
+pub(crate) fn exit_efficiency<'a>(
+    trades: impl IntoIterator<Item = (&'a Position, Excursion)>,
+) -> Option<f64> {
+    let ratios: Vec<f64> = trades
+        .into_iter()
+        .filter(|(pos, ex)| !pos.open && ex.mfe > 0.0)
+        .map(|(pos, ex)| {
+            (pos.sale_price - pos.cost_per_share) * pos.quantity * pos.multiplier / ex.mfe
+        })
+        .collect();
+    (!ratios.is_empty()).then(|| ratios.iter().sum::<f64>() / ratios.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    const HISTORY: &str = "Date,Open,High,Low,Close,Adj Close,Volume
+2026-03-02,100,104,99,103,103,1000
+2026-03-03,103,106,101,105,105,1000
+2026-03-04,null,null,null,null,null,null
+2026-03-05,105,108,96,98,98,1000
+";
+
+    #[test]
+    fn marks_open_positions_to_the_latest_close() {
+        let history = PriceHistory::parse(HISTORY).unwrap();
+        assert_eq!(history.latest(day(4)).unwrap().0, day(3));
+        assert_eq!(history.between(day(3), day(5)).count(), 2);
+
+        let mut store = PriceStore::default();
+        store.histories.insert("KO".into(), history);
+        let mut positions = vec![
+            Position {
+                ticker: "KO".into(),
+                cost_per_share: 100.0,
+                quantity: 10.0,
+                sale_price: 100.0,
+                purchase_date: day(2),
+                open: true,
+                ..Position::default()
+            },
+            Position {
+                ticker: "PEP".into(),
+                purchase_date: day(2),
+                open: true,
+                ..Position::default()
+            },
+        ];
+        mark_to_market(&mut positions, &store, day(18));
+        assert_eq!(
+            (positions[0].sale_price, positions[0].sale_date),
+            (98.0, day(5))
+        );
+        assert_eq!(positions[1].sale_date, day(18));
+
+        let path = roi_path(&positions[0], store.get("KO").unwrap());
+        assert_eq!(path, vec![(0.0, 0.0), (0.0, 3.0), (1.0, 5.0), (3.0, -2.0)]);
+        assert_eq!(
+            file_name("AAPL  260220C00150000"),
+            "AAPL260220C00150000.csv"
+        );
+    }
+
+    #[test]
+    fn quotes_only_fill_days_without_a_close() {
+        let mut store = PriceStore::default();
+        store
+            .histories
+            .insert("KO".into(), PriceHistory::parse(HISTORY).unwrap());
+        let quote = |date, price| Quote { date, price };
+        // The file already closed at 98 on the 5th.
+        store.add_quote("KO", quote(day(5), 97.0));
+        store.add_quote("KO", quote(day(6), 99.0));
+        store.add_quote("KO", quote(day(6), 101.5));
+        // An old quote for a day the file has no bar for still fills it.
+        store.add_quote("KO", quote(day(4), 104.0));
+
+        let history = store.get("KO").unwrap();
+        assert_eq!(history.latest(day(5)).unwrap().1.close, 98.0);
+        assert_eq!(history.latest(day(4)).unwrap().1.close, 104.0);
+        let (date, bar) = history.latest(day(18)).unwrap();
+        assert_eq!(date, day(6));
+        assert_eq!(
+            (bar.open, bar.low, bar.high, bar.close),
+            (99.0, 99.0, 101.5, 101.5)
+        );
+    }
+
+    #[test]
+    fn excursions_use_the_days_range() {
+        let history = PriceHistory::parse(HISTORY).unwrap();
+        let held = |sale_price, sale_date| Position {
+            ticker: "KO".into(),
+            cost_per_share: 100.0,
+            quantity: 10.0,
+            sale_price,
+            purchase_date: day(2),
+            sale_date,
+            ..Position::default()
+        };
+        let full = held(98.0, day(5));
+        let ex = excursion(&full, &history).unwrap();
+        assert_eq!((ex.mae, ex.mfe), (-40.0, 80.0));
+        assert_eq!((ex.mae_pct, ex.mfe_pct), (-0.04, 0.08));
+
+        let early = held(105.0, day(3));
+        let ex_early = excursion(&early, &history).unwrap();
+        assert_eq!((ex_early.mae, ex_early.mfe), (-10.0, 60.0));
+        assert!(excursion(&held(100.0, day(1)), &PriceHistory::default()).is_none());
+
+        let efficiency = exit_efficiency([(&full, ex), (&early, ex_early)]).unwrap();
+        assert!((efficiency - (-0.25 + 50.0 / 60.0) / 2.0).abs() < 1e-9);
+
+        // Selling at the high keeps all of the move, whatever the fees and income.
+        let at_high = Position {
+            fees: 5.0,
+            income: 3.0,
+            ..held(108.0, day(5))
+        };
+        let ex_high = excursion(&at_high, &history).unwrap();
+        assert_eq!(exit_efficiency([(&at_high, ex_high)]), Some(1.0));
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parse_date_any;
+use crate::prices::{PriceHistory, file_name};
+
+const HTTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The latest price of a symbol and the day it is for.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Quote {
+    pub(crate) date: NaiveDate,
+    pub(crate) price: f64,
+}
+
+/// Somewhere quotes can be fetched from.
+pub(crate) trait PriceSource: Send {
+    /// The symbol's latest price; `None` when the source does not know it.
+    fn quote(&self, symbol: &str) -> Result<Option<Quote>, String>;
+}
+
+/// Which source to use, as set in `roi_config.json`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub(crate) enum SourceConfig {
+    /// The last close in the price history directory.
+    #[default]
+    Csv,
+    /// A JSON endpoint. `{symbol}` in the URL is replaced by the symbol;
+    /// `price` and `date` are dotted paths into the response, with numbers
+    /// indexing arrays (`quoteResponse.result.0.regularMarketPrice`).
+    Http {
+        url: String,
+        price: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        date: Option<String>,
+        /// Extra request headers, e.g. an API key.
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        headers: BTreeMap<String, String>,
+    },
+}
+
+impl SourceConfig {
+    pub(crate) fn build(&self, price_history_dir: &str) -> Box<dyn PriceSource> {
+        match self {
+            SourceConfig::Csv => Box::new(CsvSource {
+                dir: price_history_dir.to_string(),
+            }),
+            SourceConfig::Http {
+                url,
+                price,
+                date,
+                headers,
+            } => Box::new(HttpSource {
+                url: url.clone(),
+                price: price.clone(),
+                date: date.clone(),
+                headers: headers.clone(),
+                agent: ureq::AgentBuilder::new().timeout(HTTP_TIMEOUT).build(),
+            }),
+        }
+    }
+}
+
+/// Quotes from the per-symbol CSV files in a directory.
+pub(crate) struct CsvSource {
+    dir: String,
+}
+
+impl PriceSource for CsvSource {
+    fn quote(&self, symbol: &str) -> Result<Option<Quote>, String> {
+        let path = Path::new(&self.dir).join(file_name(symbol));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = fs::read_to_string(&path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let history = PriceHistory::parse(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+        Ok(history.latest(NaiveDate::MAX).map(|(date, bar)| Quote {
+            date,
+            price: bar.close,
+        }))
+    }
+}
+
+/// Quotes from a JSON-over-HTTP endpoint.
+pub(crate) struct HttpSource {
+    url: String,
+    price: String,
+    date: Option<String>,
+    headers: BTreeMap<String, String>,
+    agent: ureq::Agent,
+}
+
+impl PriceSource for HttpSource {
+    fn quote(&self, symbol: &str) -> Result<Option<Quote>, String> {
+        let url = self.url.replace("{symbol}", &encode(symbol));
+        let mut request = self.agent.get(&url);
+        for (name, value) in &self.headers {
+            request = request.set(name, value);
+        }
+        let body = match request.call() {
+            Ok(response) => response
+                .into_string()
+                .map_err(|e| format!("{symbol}: {e}"))?,
+            Err(ureq::Error::Status(404, _)) => return Ok(None),
+            Err(err) => return Err(format!("{symbol}: {err}")),
+        };
+        let json: Value =
+            serde_json::from_str(&body).map_err(|e| format!("{symbol}: not JSON: {e}"))?;
+        let Some(price) = lookup(&json, &self.price).and_then(number) else {
+            return Ok(None);
+        };
+        let date = match &self.date {
+            Some(path) => lookup(&json, path)
+                .and_then(date)
+                .ok_or_else(|| format!("{symbol}: no date at {path}"))?,
+            None => chrono::Local::now().date_naive(),
+        };
+        Ok(Some(Quote { date, price }))
+    }
+}
+
+/// Percent-encodes everything but letters, digits and `-._~`; spaces in
+/// OCC symbols are dropped.
+fn encode(symbol: &str) -> String {
+    symbol
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '-' | '.' | '_' | '~' => c.to_string(),
+            _ => c.to_string().bytes().map(|b| format!("%{b:02X}")).collect(),
+        })
+        .collect()
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|key| !key.is_empty())
+        .try_fold(value, |value, key| match value {
+            Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+            _ => value.get(key),
+        })
+}
+
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => crate::parse_number(s),
+        _ => None,
+    }
+    .filter(|n| n.is_finite())
+}
+
+/// A date string, or a Unix timestamp in seconds or milliseconds.
+fn date(value: &Value) -> Option<NaiveDate> {
+    match value {
+        Value::String(s) => parse_date_any(s.get(..10).unwrap_or(s)).ok(),
+        Value::Number(n) => {
+            let n = n.as_i64()?;
+            let secs = if n > 100_000_000_000 { n / 1000 } else { n };
+            DateTime::from_timestamp(secs, 0).map(|d| d.date_naive())
+        }
+        _ => None,
+    }
+}
+
+/// A fetched quote and when it was fetched.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub(crate) struct CachedQuote {
+    #[serde(flatten)]
+    pub(crate) quote: Quote,
+    pub(crate) fetched: DateTime<Utc>,
+}
+
+/// Fetched quotes kept on disk, so a restart doesn't refetch them.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub(crate) struct QuoteCache {
+    pub(crate) quotes: BTreeMap<String, CachedQuote>,
+}
+
+impl QuoteCache {
+    /// A missing cache starts empty.
+    pub(crate) fn load(path: &str) -> Result<Self, String> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let data = fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}"))?;
+        serde_json::from_str(&data).map_err(|e| format!("{path}: {e}"))
+    }
+
+    pub(crate) fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("Could not save {path}: {e}"))
+    }
+
+    /// Whether the symbol was fetched less than `max_age` ago.
+    pub(crate) fn is_fresh(&self, symbol: &str, now: DateTime<Utc>, max_age: Duration) -> bool {
+        self.quotes.get(symbol).is_some_and(|cached| {
+            (now - cached.fetched)
+                .to_std()
+                .is_ok_and(|age| age < max_age)
+        })
+    }
+
+    pub(crate) fn insert(&mut self, symbol: String, quote: Quote, now: DateTime<Utc>) {
+        self.quotes.insert(
+            symbol,
+            CachedQuote {
+                quote,
+                fetched: now,
+            },
+        );
+    }
+}
+
+/// What a background refresh found.
+#[derive(Debug, Default)]
+pub(crate) struct Refreshed {
+    pub(crate) quotes: Vec<(String, Quote)>,
+    /// Symbols the source had no quote for.
+    pub(crate) missing: Vec<String>,
+    pub(crate) errors: Vec<String>,
+}
+
+/// Fetches the symbols on a separate thread; the result arrives on the
+/// returned channel once every symbol has been tried.
+pub(crate) fn spawn_refresh(
+    source: Box<dyn PriceSource>,
+    symbols: Vec<String>,
+) -> Receiver<Refreshed> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut refreshed = Refreshed::default();
+        for symbol in symbols {
+            match source.quote(&symbol) {
+                Ok(Some(quote)) => refreshed.quotes.push((symbol, quote)),
+                Ok(None) => refreshed.missing.push(symbol),
+                Err(err) => refreshed.errors.push(err),
+            }
+        }
+        // The app may have quit in the meantime.
+        let _ = tx.send(refreshed);
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves canned JSON for `/quote/KO` and 404 for anything else.
+    fn mock_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let authorized = request.to_ascii_lowercase().contains("x-api-key: secret");
+                let (status, body) = match request.split_whitespace().nth(1) {
+                    Some("/quote/KO") if authorized => {
+                        ("200 OK", r#"{"data":[{"last":"64.25","time":1760745600}]}"#)
+                    }
+                    Some("/quote/KO") => ("401 Unauthorized", "{}"),
+                    Some("/quote/BTC%2FUSD") => ("200 OK", r#"{"data":[{"time":0}]}"#),
+                    _ => ("404 Not Found", "{}"),
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    fn source(url: &str, key: &str) -> Box<dyn PriceSource> {
+        SourceConfig::Http {
+            url: format!("{url}/quote/{{symbol}}"),
+            price: "data.0.last".into(),
+            date: Some("data.0.time".into()),
+            headers: BTreeMap::from([("X-Api-Key".into(), key.into())]),
+        }
+        .build("prices")
+    }
+
+    #[test]
+    fn http_source_maps_the_response() {
+        let url = mock_server();
+        let quote = source(&url, "secret").quote("KO").unwrap().unwrap();
+        assert_eq!(quote.price, 64.25);
+        assert_eq!(quote.date, NaiveDate::from_ymd_opt(2025, 10, 18).unwrap());
+        assert_eq!(source(&url, "secret").quote("PEP").unwrap(), None);
+        // Found, but without a price.
+        assert_eq!(source(&url, "secret").quote("BTC/USD").unwrap(), None);
+        assert!(source(&url, "wrong").quote("KO").is_err());
+
+        let refreshed = spawn_refresh(source(&url, "secret"), vec!["KO".into(), "PEP".into()])
+            .recv()
+            .unwrap();
+        assert_eq!(refreshed.quotes, vec![("KO".to_string(), quote)]);
+        assert_eq!(refreshed.missing, vec!["PEP".to_string()]);
+
+        let now = Utc::now();
+        let mut cache = QuoteCache::default();
+        cache.insert("KO".into(), quote, now - chrono::Duration::minutes(20));
+        assert!(cache.is_fresh("KO", now, Duration::from_secs(30 * 60)));
+        assert!(!cache.is_fresh("KO", now, Duration::from_secs(10 * 60)));
+        assert!(!cache.is_fresh("PEP", now, Duration::from_secs(30 * 60)));
+    }
+}
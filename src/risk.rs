@@ -0,0 +1,259 @@
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+use crate::Position;
+
+/// Trading days used to annualize daily figures.
+pub(crate) const TRADING_DAYS: f64 = 252.0;
+
+/// One weekday's realized PnL over the capital deployed that day.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct DailyReturn {
+    pub(crate) date: NaiveDate,
+    pub(crate) pnl: f64,
+    pub(crate) capital: f64,
+}
+
+impl DailyReturn {
+    pub(crate) fn value(&self) -> f64 {
+        self.pnl / self.capital
+    }
+}
+
+/// Weekdays with capital in closed positions, from the first purchase to
+/// the last sale. A position's PnL lands on its sale date (a weekend sale on
+/// the Friday before); open positions are left out until they are sold.
+pub(crate) fn daily_returns(positions: &[&Position]) -> Vec<DailyReturn> {
+    let closed: Vec<&&Position> = positions.iter().filter(|p| !p.open).collect();
+    let (Some(first), Some(last)) = (
+        closed.iter().map(|p| p.purchase_date).min(),
+        closed
+            .iter()
+            .map(|p| weekday_on_or_before(p.sale_date))
+            .max(),
+    ) else {
+        return Vec::new();
+    };
+    first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .filter(|date| !is_weekend(*date))
+        .filter_map(|date| {
+            let held = closed
+                .iter()
+                .filter(|p| p.purchase_date <= date && date <= p.sale_date.max(p.purchase_date));
+            let capital: f64 = held.clone().map(|p| p.invested()).sum();
+            let pnl: f64 = held
+                .filter(|p| weekday_on_or_before(p.sale_date) == date)
+                .map(|p| p.roi_value())
+                .sum();
+            (capital > 0.0).then_some(DailyReturn { date, pnl, capital })
+        })
+        .collect()
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+fn weekday_on_or_before(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date - Duration::days(2),
+        _ => date,
+    }
+}
+
+/// Annualized risk and return figures for a daily return series. Ratios are
+/// `None` when their denominator is zero or there are fewer than two days.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct RiskMetrics {
+    pub(crate) days: usize,
+    /// Compound annual growth of the daily series.
+    pub(crate) annual_return: f64,
+    /// Standard deviation of daily returns, annualized.
+    pub(crate) volatility: f64,
+    /// Root mean square of returns below the daily risk-free rate, annualized.
+    pub(crate) downside_deviation: f64,
+    /// Largest fall from a peak of the compounded series, as a positive fraction.
+    pub(crate) max_drawdown: f64,
+    pub(crate) sharpe: Option<f64>,
+    pub(crate) sortino: Option<f64>,
+    pub(crate) calmar: Option<f64>,
+}
+
+/// `risk_free_rate` is annual, e.g. 0.04 for 4%.
+pub(crate) fn risk_metrics(returns: &[DailyReturn], risk_free_rate: f64) -> RiskMetrics {
+    let n = returns.len();
+    if n < 2 {
+        return RiskMetrics {
+            days: n,
+            ..RiskMetrics::default()
+        };
+    }
+    let values: Vec<f64> = returns.iter().map(DailyReturn::value).collect();
+    let mean = values.iter().sum::<f64>() / n as f64;
+    let variance = values.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+    let daily_rf = risk_free_rate / TRADING_DAYS;
+    let downside = (values
+        .iter()
+        .map(|r| (r - daily_rf).min(0.0).powi(2))
+        .sum::<f64>()
+        / n as f64)
+        .sqrt();
+
+    let (mut growth, mut peak, mut max_drawdown) = (1.0_f64, 1.0_f64, 0.0_f64);
+    for r in &values {
+        growth *= 1.0 + r;
+        peak = peak.max(growth);
+        max_drawdown = max_drawdown.max(1.0 - growth / peak);
+    }
+    let annual_return = growth.max(0.0).powf(TRADING_DAYS / n as f64) - 1.0;
+
+    let annualize = TRADING_DAYS.sqrt();
+    let volatility = variance.sqrt() * annualize;
+    let downside_deviation = downside * annualize;
+    let excess = (mean - daily_rf) * TRADING_DAYS;
+    let ratio = |num: f64, den: f64| (den > f64::EPSILON).then(|| num / den);
+    RiskMetrics {
+        days: n,
+        annual_return,
+        volatility,
+        downside_deviation,
+        max_drawdown,
+        sharpe: ratio(excess, volatility),
+        sortino: ratio(excess, downside_deviation),
+        calmar: ratio(annual_return, max_drawdown),
+    }
+}
+
+/// Labels of the R histogram's buckets: under -2R, 1R wide up to 3R, then
+/// 3R and over.
+pub(crate) const R_BUCKETS: [&str; 7] = ["<-2", "-2", "-1", "0", "1", "2", "3+"];
+
+/// R-multiples of closed trades with a stop.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct RStats {
+    pub(crate) trades: usize,
+    pub(crate) win_rate: f64,
+    pub(crate) avg_win: f64,
+    /// Negative.
+    pub(crate) avg_loss: f64,
+    /// Average R per trade: win rate × average win + loss rate × average loss.
+    pub(crate) expectancy: f64,
+    pub(crate) total: f64,
+    /// Trades per bucket of `R_BUCKETS`.
+    pub(crate) histogram: [u64; 7],
+}
+
+pub(crate) fn r_stats(r_multiples: &[f64]) -> RStats {
+    let trades = r_multiples.len();
+    if trades == 0 {
+        return RStats::default();
+    }
+    let average = |rs: Vec<f64>| {
+        if rs.is_empty() {
+            0.0
+        } else {
+            rs.iter().sum::<f64>() / rs.len() as f64
+        }
+    };
+    let wins: Vec<f64> = r_multiples.iter().copied().filter(|r| *r > 0.0).collect();
+    let losses: Vec<f64> = r_multiples.iter().copied().filter(|r| *r < 0.0).collect();
+    let win_rate = wins.len() as f64 / trades as f64;
+    let loss_rate = losses.len() as f64 / trades as f64;
+    let (avg_win, avg_loss) = (average(wins), average(losses));
+    let mut histogram = [0; 7];
+    for r in r_multiples {
+        histogram[(r.floor() + 3.0).clamp(0.0, 6.0) as usize] += 1;
+    }
+    RStats {
+        trades,
+        win_rate,
+        avg_win,
+        avg_loss,
+        expectancy: win_rate * avg_win + loss_rate * avg_loss,
+        total: r_multiples.iter().sum(),
+        histogram,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn returns_are_pnl_over_capital_on_weekdays() {
+        let trade = |cost_per_share, sale_price, purchase_date, sale_date| Position {
+            ticker: "KO".into(),
+            cost_per_share,
+            quantity: 10.0,
+            sale_price,
+            purchase_date,
+            sale_date,
+            ..Position::default()
+        };
+        // Mon 2 – Wed 4, and Tue 3 – Sat 7 (booked on Fri 6).
+        let first = trade(100.0, 130.0, day(2), day(4));
+        let second = trade(50.0, 45.0, day(3), day(7));
+        let open = Position {
+            open: true,
+            ..trade(10.0, 20.0, day(2), day(9))
+        };
+        let returns = daily_returns(&[&first, &second, &open]);
+        let summary: Vec<(u32, f64, f64)> = returns
+            .iter()
+            .map(|r| (r.date.day(), r.pnl, r.capital))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (2, 0.0, 1000.0),
+                (3, 0.0, 1500.0),
+                (4, 300.0, 1500.0),
+                (5, 0.0, 500.0),
+                (6, -50.0, 500.0),
+            ]
+        );
+
+        let metrics = risk_metrics(&returns, 0.0);
+        assert_eq!(metrics.days, 5);
+        assert!((metrics.max_drawdown - 0.1).abs() < 1e-9);
+        assert!(metrics.sharpe.unwrap() > 0.0);
+        assert!(metrics.sortino.unwrap() > metrics.sharpe.unwrap());
+        assert!(metrics.calmar.is_some());
+        assert_eq!(risk_metrics(&returns[..1], 0.0).sharpe, None);
+    }
+
+    #[test]
+    fn r_multiples_come_from_the_stop() {
+        let trade = Position {
+            ticker: "KO".into(),
+            cost_per_share: 100.0,
+            quantity: 10.0,
+            sale_price: 112.0,
+            stop_price: Some(96.0),
+            target_price: Some(110.0),
+            ..Position::default()
+        };
+        assert_eq!(trade.initial_risk(), Some(4.0));
+        assert_eq!(trade.r_multiple(), Some(3.0));
+        assert_eq!(trade.reward_risk(), Some(2.5));
+        let no_stop = Position {
+            stop_price: None,
+            ..trade.clone()
+        };
+        assert_eq!(no_stop.r_multiple(), None);
+
+        let stats = r_stats(&[3.0, -1.0, -1.0, 0.5, -2.5]);
+        assert_eq!(stats.trades, 5);
+        assert!((stats.expectancy - (-0.2)).abs() < 1e-9);
+        assert!((stats.avg_win - 1.75).abs() < 1e-9);
+        assert!((stats.avg_loss + 1.5).abs() < 1e-9);
+        assert_eq!(stats.histogram, [1, 0, 2, 1, 0, 0, 1]);
+    }
+}
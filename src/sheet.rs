@@ -0,0 +1,165 @@
+use calamine::{Data, Reader, open_workbook_auto};
+use chrono::NaiveDate;
+
+use crate::import::{NativeCell, NativeCells};
+
+/// Extensions read as spreadsheets rather than CSV.
+pub(crate) const WORKBOOK_EXTENSIONS: [&str; 4] = ["xlsx", "xlsm", "xls", "ods"];
+
+/// A worksheet as text records, plus the typed values of its number and date
+/// cells so they skip string parsing.
+pub(crate) struct SheetRecords {
+    pub(crate) records: Vec<(usize, Vec<String>)>,
+    pub(crate) native: NativeCells,
+}
+
+pub(crate) fn sheet_names(path: &str) -> Result<Vec<String>, String> {
+    let workbook = open_workbook_auto(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    let names = workbook.sheet_names();
+    if names.is_empty() {
+        return Err(format!("{path} has no worksheets"));
+    }
+    Ok(names)
+}
+
+/// Reads one worksheet. Line numbers are the spreadsheet's row numbers so
+/// diagnostics point at the row the user sees; blank rows are dropped.
+pub(crate) fn read_sheet(path: &str, sheet: &str) -> Result<SheetRecords, String> {
+    let mut workbook =
+        open_workbook_auto(path).map_err(|e| format!("Failed to open {path}: {e}"))?;
+    let range = workbook
+        .worksheet_range(sheet)
+        .map_err(|e| format!("Failed to read sheet {sheet}: {e}"))?;
+    let (first_row, first_col) = range.start().unwrap_or((0, 0));
+
+    let mut sheet = SheetRecords {
+        records: Vec::new(),
+        native: NativeCells::new(),
+    };
+    for (idx, row) in range.rows().enumerate() {
+        if row.iter().all(|cell| cell_text(cell).is_empty()) {
+            continue;
+        }
+        let line = first_row as usize + idx + 1;
+        // Keep columns aligned with the sheet when the used range starts past A.
+        let mut fields = vec![String::new(); first_col as usize];
+        for (offset, cell) in row.iter().enumerate() {
+            let col = first_col as usize + offset;
+            if let Some(value) = native_value(cell) {
+                sheet.native.insert((line, col), value);
+            }
+            fields.push(cell_text(cell));
+        }
+        sheet.records.push((line, fields));
+    }
+    Ok(sheet)
+}
+
+fn native_value(cell: &Data) -> Option<NativeCell> {
+    match cell {
+        Data::Int(n) => Some(NativeCell::Number(*n as f64)),
+        Data::Float(n) => Some(NativeCell::Number(*n)),
+        Data::DateTime(dt) => dt.as_datetime().map(|d| NativeCell::Date(d.date())),
+        Data::DateTimeIso(s) => iso_date(s).map(NativeCell::Date),
+        _ => None,
+    }
+}
+
+/// Text used for header detection, profile signatures and the mapping wizard.
+fn cell_text(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) => s.trim().to_string(),
+        Data::Int(n) => n.to_string(),
+        Data::Float(n) => n.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => match dt.as_datetime() {
+            Some(d) => d.date().format("%Y-%m-%d").to_string(),
+            None => dt.to_string(),
+        },
+        Data::DateTimeIso(s) | Data::DurationIso(s) => match iso_date(s) {
+            Some(d) => d.format("%Y-%m-%d").to_string(),
+            None => s.clone(),
+        },
+    }
+}
+
+fn iso_date(s: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(s.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::{
+        ColumnMap, ExtraColumns, ImportProfile, ImportReport, ValueFormats, header_signature,
+        parse_import_records,
+    };
+
+    fn fixture() -> String {
+        format!(
+            "{}/tests/fixtures/trade_journal.xlsx",
+            env!("CARGO_MANIFEST_DIR")
+        )
+    }
+
+    fn parse(profiles: &[ImportProfile]) -> ImportReport {
+        let sheet = read_sheet(&fixture(), "Trades").expect("sheet reads");
+        let today = NaiveDate::from_ymd_opt(2026, 3, 31).unwrap();
+        parse_import_records(&sheet.records, &sheet.native, profiles, today)
+    }
+
+    #[test]
+    fn lists_sheets_in_workbook_order() {
+        assert_eq!(sheet_names(&fixture()).unwrap(), vec!["Notes", "Trades"]);
+    }
+
+    #[test]
+    fn date_cells_import_without_text_parsing() {
+        let report = parse(&[]);
+        assert_eq!(report.rows.len(), 2);
+        let aapl = &report.rows[0];
+        assert_eq!(aapl.line, 2);
+        assert_eq!(
+            aapl.position.purchase_date,
+            NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()
+        );
+        assert_eq!(
+            aapl.position.sale_date,
+            NaiveDate::from_ymd_opt(2026, 2, 10).unwrap()
+        );
+        assert_eq!(report.rows[1].position.quantity, 2.5);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].line, 4);
+    }
+
+    #[test]
+    fn native_cells_ignore_profile_text_formats() {
+        let sheet = read_sheet(&fixture(), "Trades").unwrap();
+        let profile = ImportProfile {
+            name: "journal".into(),
+            signature: header_signature(&sheet.records[0].1),
+            columns: ColumnMap {
+                ticker: 0,
+                cost: 1,
+                qty: 2,
+                sale_price: 3,
+                buy_date: 4,
+                sale_date: 5,
+                lot_id: None,
+                extras: ExtraColumns::default(),
+            },
+            formats: ValueFormats {
+                date_format: Some("%d.%m.%Y".into()),
+                decimal_comma: true,
+            },
+        };
+        let report = parse(&[profile]);
+        assert_eq!(report.rows.len(), 2);
+        assert!((report.rows[0].position.cost_per_share - 150.25).abs() < 1e-9);
+        assert_eq!(
+            report.rows[1].position.sale_date,
+            NaiveDate::from_ymd_opt(2026, 3, 2).unwrap()
+        );
+    }
+}
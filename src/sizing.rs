@@ -0,0 +1,174 @@
+use crate::input::{InputKind, TextInput};
+use crate::parse_f64;
+
+/// Quantity for a trade that loses a set share of the account at its stop.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Sizing {
+    /// Shares, contracts or coins, rounded down to the instrument's decimals.
+    pub(crate) quantity: f64,
+    /// Loss if the stop is hit; at most the risk budget.
+    pub(crate) risk: f64,
+    pub(crate) value: f64,
+    /// Position value over account size.
+    pub(crate) account_pct: f64,
+}
+
+/// `risk_pct` is in percent, e.g. 1 for 1% of the account. `multiplier` is
+/// the contract size, 1 for shares; `decimals` is how finely the quantity
+/// can be split, 0 for whole contracts and 8 for most crypto.
+pub(crate) fn size_position(
+    account: f64,
+    risk_pct: f64,
+    entry: f64,
+    stop: f64,
+    multiplier: f64,
+    decimals: usize,
+) -> Result<Sizing, String> {
+    if account <= 0.0 {
+        return Err("Account size must be positive".into());
+    }
+    if risk_pct <= 0.0 || risk_pct > 100.0 {
+        return Err("Risk % must be between 0 and 100".into());
+    }
+    if stop >= entry {
+        return Err("Stop must be below the entry".into());
+    }
+    let per_unit = (entry - stop) * multiplier;
+    let budget = account * risk_pct / 100.0;
+    let scale = 10f64.powi(decimals as i32);
+    // A little slack so 100 / 1.0 stays 100 despite rounding.
+    let quantity = (budget / per_unit * scale + 1e-9).floor() / scale;
+    if quantity <= 0.0 {
+        return Err(format!(
+            "A quantity of {} risks {:.2}, more than the {budget:.2} budget",
+            1.0 / scale,
+            per_unit / scale
+        ));
+    }
+    let value = quantity * entry * multiplier;
+    Ok(Sizing {
+        quantity,
+        risk: quantity * per_unit,
+        value,
+        account_pct: value / account,
+    })
+}
+
+/// The calculator's inputs.
+#[derive(Clone, Debug)]
+pub(crate) struct SizingForm {
+    pub(crate) fields: Vec<(&'static str, &'static str, TextInput)>,
+    pub(crate) active: usize,
+    pub(crate) error: Option<String>,
+}
+
+impl SizingForm {
+    /// Starts with the account size filled in.
+    pub(crate) fn new(account: f64) -> Self {
+        let mut fields = vec![
+            (
+                "Ticker",
+                "optional, e.g. AAPL",
+                TextInput::new(InputKind::Text),
+            ),
+            (
+                "Account size",
+                "e.g. 25000",
+                TextInput::new(InputKind::Number),
+            ),
+            ("Risk %", "e.g. 1", TextInput::new(InputKind::Number)),
+            ("Entry", "e.g. 112.40", TextInput::new(InputKind::Number)),
+            ("Stop", "e.g. 108.00", TextInput::new(InputKind::Number)),
+        ];
+        fields[1].2.set_value(format!("{account:.2}"));
+        Self {
+            fields,
+            active: 0,
+            error: None,
+        }
+    }
+
+    pub(crate) fn value(&self, idx: usize) -> &str {
+        self.fields[idx].2.value()
+    }
+
+    pub(crate) fn active_input(&mut self) -> &mut TextInput {
+        &mut self.fields[self.active].2
+    }
+
+    pub(crate) fn next_field(&mut self) {
+        self.active = (self.active + 1) % self.fields.len();
+    }
+
+    pub(crate) fn prev_field(&mut self) {
+        self.active = (self.active + self.fields.len() - 1) % self.fields.len();
+    }
+
+    pub(crate) fn entry(&self) -> Result<f64, String> {
+        parse_f64(self.value(3), "entry")
+    }
+
+    pub(crate) fn stop(&self) -> Result<f64, String> {
+        parse_f64(self.value(4), "stop")
+    }
+
+    pub(crate) fn compute(&self, multiplier: f64, decimals: usize) -> Result<Sizing, String> {
+        size_position(
+            parse_f64(self.value(1), "account size")?,
+            parse_f64(self.value(2), "risk %")?,
+            self.entry()?,
+            self.stop()?,
+            multiplier,
+            decimals,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_to_the_risk_budget() {
+        // 1% of 25,000 is 250; 4.40 a share at risk buys 56 whole shares.
+        let sizing = size_position(25_000.0, 1.0, 112.40, 108.0, 1.0, 0).unwrap();
+        assert_eq!(sizing.quantity, 56.0);
+        assert!((sizing.risk - 246.4).abs() < 1e-9);
+        assert!((sizing.value - 6294.4).abs() < 1e-9);
+        assert!((sizing.account_pct - 0.251776).abs() < 1e-9);
+
+        assert_eq!(
+            size_position(10_000.0, 1.0, 101.0, 100.0, 1.0, 0)
+                .unwrap()
+                .quantity,
+            100.0
+        );
+        // An ES contract risks 50 per point.
+        let futures = size_position(50_000.0, 2.0, 5000.0, 4990.0, 50.0, 0).unwrap();
+        assert_eq!(futures.quantity, 2.0);
+        assert!(size_position(1_000.0, 1.0, 100.0, 80.0, 1.0, 0).is_err());
+        assert!(size_position(10_000.0, 1.0, 100.0, 101.0, 1.0, 0).is_err());
+        // Fractional shares round down to the cent of a share.
+        let fractional = size_position(25_000.0, 1.0, 112.40, 108.0, 1.0, 2).unwrap();
+        assert_eq!(fractional.quantity, 56.81);
+        assert!(fractional.risk <= 250.0);
+
+        let mut form = SizingForm::new(25_000.0);
+        form.fields[2].2.set_value("1");
+        form.fields[3].2.set_value("112.40");
+        form.fields[4].2.set_value("108");
+        assert_eq!(form.compute(1.0, 0).unwrap(), sizing);
+    }
+
+    #[test]
+    fn sizes_crypto_in_fractions() {
+        // A 250 budget can't risk a whole BTC 2,000 below the entry.
+        assert!(size_position(25_000.0, 1.0, 60_000.0, 58_000.0, 1.0, 0).is_err());
+        let btc = size_position(25_000.0, 1.0, 60_000.0, 58_000.0, 1.0, 8).unwrap();
+        assert_eq!(btc.quantity, 0.125);
+        assert!((btc.risk - 250.0).abs() < 1e-9);
+        assert!((btc.value - 7_500.0).abs() < 1e-9);
+        // 1e-8 BTC still risks more than a 0.00001 budget.
+        assert!(size_position(1.0, 0.001, 60_000.0, 58_000.0, 1.0, 8).is_err());
+    }
+}
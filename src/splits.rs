@@ -0,0 +1,281 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::import::sanitize_header;
+use crate::{Position, parse_date_any, parse_ticker};
+
+/// A stock split or reverse split. `ratio` is new shares per old share:
+/// 2 for a 2-for-1 split, 0.1 for a 1-for-10 reverse split.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Split {
+    pub(crate) ticker: String,
+    pub(crate) date: NaiveDate,
+    pub(crate) ratio: f64,
+}
+
+impl Split {
+    /// The ratio as "new:old", e.g. "2:1" or "1:10".
+    pub(crate) fn describe(&self) -> String {
+        if self.ratio >= 1.0 {
+            format!("{}:1", trim_float(self.ratio))
+        } else {
+            format!("1:{}", trim_float(1.0 / self.ratio))
+        }
+    }
+}
+
+fn trim_float(v: f64) -> String {
+    let s = format!("{v:.4}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Quantity and prices as they were entered, kept while split adjustments
+/// replace them on the position.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub(crate) struct EnteredValues {
+    pub(crate) cost_per_share: f64,
+    pub(crate) quantity: f64,
+    pub(crate) sale_price: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) stop_price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) target_price: Option<f64>,
+}
+
+/// Reads "2:1", "2-for-1", "3 for 2", "1/10" or a plain number like "0.5".
+pub(crate) fn parse_ratio(raw: &str) -> Option<f64> {
+    let lower = raw.trim().to_lowercase();
+    let parts: Vec<&str> = lower
+        .split(|c: char| c == ':' || c == '/' || c == '-' || c.is_whitespace())
+        .filter(|p| !p.is_empty() && *p != "for")
+        .collect();
+    let ratio = match parts[..] {
+        [single] => single.parse::<f64>().ok()?,
+        [new, old] => new.parse::<f64>().ok()? / old.parse::<f64>().ok()?,
+        _ => return None,
+    };
+    (ratio.is_finite() && ratio > 0.0).then_some(ratio)
+}
+
+/// Combined ratio of the ticker's splits after `date`, skipping any the
+/// values already reflect (those on or before `through`).
+fn factor_after(
+    splits: &[Split],
+    ticker: &str,
+    date: NaiveDate,
+    through: Option<NaiveDate>,
+) -> f64 {
+    let after = through.map_or(date, |t| t.max(date));
+    splits
+        .iter()
+        .filter(|s| s.ticker.eq_ignore_ascii_case(ticker) && s.date > after)
+        .map(|s| s.ratio)
+        .product()
+}
+
+/// Restates every position on today's share basis: the entered quantity and
+/// cost are adjusted for splits after the purchase date, the sale price for
+/// splits after the sale date. The entered values are kept on the position
+/// while they differ, so the adjustment can be redone when splits change.
+pub(crate) fn apply_splits(positions: &mut [Position], splits: &[Split]) {
+    // Options are adjusted by the exchange with new contract terms instead.
+    for pos in positions.iter_mut().filter(|p| p.option.is_none()) {
+        let entered = pos.entered.take().unwrap_or(EnteredValues {
+            cost_per_share: pos.cost_per_share,
+            quantity: pos.quantity,
+            sale_price: pos.sale_price,
+            stop_price: pos.stop_price,
+            target_price: pos.target_price,
+        });
+        let bought = factor_after(splits, &pos.ticker, pos.purchase_date, pos.adjusted_through);
+        let sold = factor_after(splits, &pos.ticker, pos.sale_date, pos.adjusted_through);
+        pos.quantity = entered.quantity * bought;
+        pos.cost_per_share = entered.cost_per_share / bought;
+        pos.sale_price = entered.sale_price / sold;
+        // The stop and target were planned at entry, on the purchase basis.
+        pos.stop_price = entered.stop_price.map(|p| p / bought);
+        pos.target_price = entered.target_price.map(|p| p / bought);
+        if bought != 1.0 || sold != 1.0 {
+            pos.entered = Some(entered);
+        }
+    }
+}
+
+/// Adds splits not already listed for the same ticker and date. Returns how
+/// many were added.
+pub(crate) fn merge_splits(existing: &mut Vec<Split>, new: Vec<Split>) -> usize {
+    let before = existing.len();
+    for split in new {
+        if !existing
+            .iter()
+            .any(|s| s.ticker == split.ticker && s.date == split.date)
+        {
+            existing.push(split);
+        }
+    }
+    existing.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.ticker.cmp(&b.ticker)));
+    existing.len() - before
+}
+
+/// Reads a splits CSV (ticker, date and ratio columns). Returns `None` when
+/// the first row is not such a header, so other importers can try the file.
+pub(crate) fn parse_split_records(
+    records: &[(usize, Vec<String>)],
+) -> Option<Result<Vec<Split>, String>> {
+    let (_, header) = records.first()?;
+    let find = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.contains(&sanitize_header(h).as_str()))
+    };
+    let ticker = find(&["ticker", "symbol"])?;
+    let date = find(&["date", "splitdate", "exdate", "effectivedate"])?;
+    let ratio = find(&["ratio", "splitratio", "split"])?;
+
+    let mut splits = Vec::new();
+    for (line, fields) in &records[1..] {
+        let field = |i: usize| fields.get(i).map(String::as_str).unwrap_or("");
+        let parsed = parse_ticker(field(ticker)).ok().zip(
+            parse_date_any(field(date))
+                .ok()
+                .zip(parse_ratio(field(ratio))),
+        );
+        match parsed {
+            Some((ticker, (date, ratio))) => splits.push(Split {
+                ticker,
+                date,
+                ratio,
+            }),
+            None => return Some(Err(format!("Line {line}: expected ticker, date and ratio"))),
+        }
+    }
+    Some(Ok(splits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn parses_ratio_notations() {
+        assert_eq!(parse_ratio("2:1"), Some(2.0));
+        assert_eq!(parse_ratio("3 for 2"), Some(1.5));
+        assert_eq!(parse_ratio("1-for-10"), Some(0.1));
+        assert_eq!(parse_ratio("1/4"), Some(0.25));
+        assert_eq!(parse_ratio("4"), Some(4.0));
+        assert_eq!(parse_ratio("0:1"), None);
+        assert_eq!(parse_ratio("abc"), None);
+    }
+
+    #[test]
+    fn adjusts_entered_values_and_undoes_when_split_removed() {
+        // 10 shares bought at $500 before a 2:1 split, sold at $260 after it.
+        let mut positions = vec![Position {
+            ticker: "NVDA".into(),
+            cost_per_share: 500.0,
+            quantity: 10.0,
+            sale_price: 260.0,
+            purchase_date: day(2026, 1, 5),
+            sale_date: day(2026, 3, 2),
+            ..Position::default()
+        }];
+        let splits = vec![Split {
+            ticker: "NVDA".into(),
+            date: day(2026, 2, 1),
+            ratio: 2.0,
+        }];
+        apply_splits(&mut positions, &splits);
+        let pos = &positions[0];
+        assert_eq!(pos.quantity, 20.0);
+        assert_eq!(pos.cost_per_share, 250.0);
+        assert_eq!(pos.sale_price, 260.0);
+        assert!((pos.roi_value() - 200.0).abs() < 1e-9);
+        assert_eq!(pos.entered.map(|e| e.quantity), Some(10.0));
+
+        apply_splits(&mut positions, &splits);
+        assert_eq!(positions[0].quantity, 20.0);
+        apply_splits(&mut positions, &[]);
+        assert_eq!(positions[0].quantity, 10.0);
+        assert_eq!(positions[0].entered, None);
+    }
+
+    #[test]
+    fn imported_values_skip_splits_they_already_reflect() {
+        let mut positions = vec![Position {
+            ticker: "NVDA".into(),
+            quantity: 20.0,
+            purchase_date: day(2026, 1, 5),
+            sale_date: day(2026, 3, 2),
+            adjusted_through: Some(day(2026, 4, 1)),
+            ..Position::default()
+        }];
+        let splits = vec![Split {
+            ticker: "NVDA".into(),
+            date: day(2026, 2, 1),
+            ratio: 2.0,
+        }];
+        apply_splits(&mut positions, &splits);
+        assert_eq!(positions[0].quantity, 20.0);
+    }
+
+    #[test]
+    fn replacing_an_adjusted_lot_keeps_the_imported_values() {
+        let splits = vec![Split {
+            ticker: "NVDA".into(),
+            date: day(2026, 2, 1),
+            ratio: 2.0,
+        }];
+        let mut positions = vec![Position {
+            ticker: "NVDA".into(),
+            cost_per_share: 500.0,
+            quantity: 10.0,
+            sale_price: 260.0,
+            purchase_date: day(2026, 1, 5),
+            sale_date: day(2026, 3, 2),
+            ..Position::default()
+        }];
+        apply_splits(&mut positions, &splits);
+        assert!(positions[0].entered.is_some());
+
+        // The broker reports the lot on today's basis, at a corrected cost.
+        let imported = Position {
+            cost_per_share: 245.0,
+            quantity: 20.0,
+            sale_price: 262.0,
+            adjusted_through: Some(day(2026, 4, 1)),
+            entered: None,
+            ..positions[0].clone()
+        };
+        positions[0].update_from_import(imported);
+        apply_splits(&mut positions, &splits);
+        let pos = &positions[0];
+        assert_eq!(
+            (pos.cost_per_share, pos.quantity, pos.sale_price),
+            (245.0, 20.0, 262.0)
+        );
+        assert_eq!(pos.entered, None);
+    }
+
+    #[test]
+    fn reads_split_csv_records() {
+        let records = vec![
+            (
+                1,
+                vec!["Symbol".into(), "Date".into(), "Split Ratio".into()],
+            ),
+            (2, vec!["nvda".into(), "2024-06-10".into(), "10:1".into()]),
+            (3, vec!["GE".into(), "08/02/2021".into(), "1-for-8".into()]),
+        ];
+        let splits = parse_split_records(&records).unwrap().unwrap();
+        assert_eq!(splits.len(), 2);
+        assert_eq!(splits[0].ticker, "NVDA");
+        assert_eq!(splits[1].describe(), "1:8");
+
+        let trades = vec![(1, vec!["Symbol".into(), "Qty".into()])];
+        assert!(parse_split_records(&trades).is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use chrono::Months;
+use serde::Serialize;
+
+use crate::Position;
+
+/// Realized gains that are taxable, split by holding period.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub(crate) struct TaxableGains {
+    /// Closed within a year of purchase.
+    pub(crate) short_term: f64,
+    /// Held for more than a year.
+    pub(crate) long_term: f64,
+    /// Closed positions left out because their account is tax-advantaged.
+    pub(crate) sheltered: usize,
+}
+
+/// Sums price gains net of fees on closed positions, leaving out accounts
+/// for which `sheltered` is true. Dividends are not included.
+pub(crate) fn taxable_gains(
+    positions: &[&Position],
+    sheltered: impl Fn(&str) -> bool,
+) -> TaxableGains {
+    let mut gains = TaxableGains::default();
+    for pos in positions.iter().filter(|p| !p.open) {
+        if pos.account.as_deref().is_some_and(&sheltered) {
+            gains.sheltered += 1;
+            continue;
+        }
+        let gain = pos.proceeds() - pos.invested() - pos.fees;
+        let one_year = pos.purchase_date + Months::new(12);
+        if pos.sale_date > one_year {
+            gains.long_term += gain;
+        } else {
+            gains.short_term += gain;
+        }
+    }
+    gains
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn closed(account: Option<&str>, bought: (i32, u32, u32), sold: (i32, u32, u32)) -> Position {
+        let date = |(y, m, d)| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        Position {
+            ticker: "AAPL".into(),
+            cost_per_share: 100.0,
+            quantity: 10.0,
+            sale_price: 110.0,
+            purchase_date: date(bought),
+            sale_date: date(sold),
+            fees: 2.0,
+            account: account.map(String::from),
+            ..Position::default()
+        }
+    }
+
+    #[test]
+    fn tax_advantaged_accounts_are_left_out() {
+        let mut dividend = closed(Some("Taxable"), (2025, 1, 2), (2025, 6, 2));
+        dividend.income = 50.0;
+        let mut open = closed(None, (2025, 1, 2), (2025, 1, 2));
+        open.open = true;
+        let positions = [
+            dividend,
+            // Exactly a year is still short-term.
+            closed(None, (2025, 1, 2), (2026, 1, 2)),
+            closed(None, (2025, 1, 2), (2026, 1, 3)),
+            closed(Some("ira"), (2025, 1, 2), (2025, 3, 2)),
+            open,
+        ];
+        let refs: Vec<&Position> = positions.iter().collect();
+        let gains = taxable_gains(&refs, |a| a.eq_ignore_ascii_case("IRA"));
+        assert_eq!(
+            gains,
+            TaxableGains {
+                short_term: 196.0,
+                long_term: 98.0,
+                sheltered: 1,
+            }
+        );
+    }
+}
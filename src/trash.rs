@@ -0,0 +1,67 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::Position;
+
+/// A deleted position, kept in the data file until restored or purged.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct TrashEntry {
+    pub(crate) position: Position,
+    pub(crate) deleted_at: DateTime<Utc>,
+}
+
+impl TrashEntry {
+    pub(crate) fn new(position: Position) -> Self {
+        Self {
+            position,
+            deleted_at: Utc::now(),
+        }
+    }
+}
+
+/// Drops entries deleted more than `days` ago; 0 keeps everything.
+/// Returns how many were purged.
+pub(crate) fn purge_expired(trash: &mut Vec<TrashEntry>, days: u32, now: DateTime<Utc>) -> usize {
+    if days == 0 {
+        return 0;
+    }
+    let cutoff = now - Duration::days(i64::from(days));
+    let before = trash.len();
+    trash.retain(|entry| entry.deleted_at >= cutoff);
+    before - trash.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn purges_entries_past_the_retention_period() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 31, 12, 0, 0).unwrap();
+        let deleted = |age: Duration| TrashEntry {
+            position: Position::default(),
+            deleted_at: now - age,
+        };
+        let trash = vec![
+            deleted(Duration::days(29)),
+            // Exactly 30 days old is still kept.
+            deleted(Duration::days(30)),
+            deleted(Duration::days(30) + Duration::seconds(1)),
+            deleted(Duration::days(400)),
+        ];
+
+        let mut kept = trash.clone();
+        assert_eq!(purge_expired(&mut kept, 30, now), 2);
+        let left: Vec<i64> = kept
+            .iter()
+            .map(|e| (now - e.deleted_at).num_days())
+            .collect();
+        assert_eq!(left, vec![29, 30]);
+
+        let mut forever = trash.clone();
+        assert_eq!(purge_expired(&mut forever, 0, now), 0);
+        assert_eq!(forever.len(), 4);
+    }
+}